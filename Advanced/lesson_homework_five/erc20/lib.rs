@@ -1,8 +1,10 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use ink_lang as ink;
+
 #[ink::contract]
 mod erc20 {
-    use ink::storage::Mapping;
+    use ink_storage::{traits::SpreadAllocate, Mapping};
 
     /// Specify ERC-20 error type.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -38,7 +40,7 @@ mod erc20 {
 
     /// Create storage for a simple ERC-20 contract.
     #[ink(storage)]
-    #[derive(Default)]
+    #[derive(SpreadAllocate)]
     pub struct Erc20 {
         /// Total token supply.
         total_supply: Balance,
@@ -53,21 +55,10 @@ mod erc20 {
         /// Create a new ERC-20 contract with an initial supply.
         #[ink(constructor)]
         pub fn new(total_supply: Balance) -> Self {
-            let mut balances = Mapping::default();
-            let caller = Self::env().caller();
-            balances.insert(&caller, &total_supply);
-            Self::env().emit_event(Transfer {
-                from: None,
-                to: Some(caller),
-                value: total_supply,
-            });
-            //可以直接把日志输出到节点的日志上去
-            ink_env::debug_println!("erc2.0 contract AccountId：{:?} | balances: {:?}", caller, balances);
-            Self {
-                total_supply,
-                balances,
-                allowances: Default::default(),
-            }
+            // 含有 Mapping 的存储需要用 initialize_contract 初始化
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                contract.new_init(total_supply)
+            })
         }
 
         /// Initialize the ERC-20 contract with the specified initial supply.
@@ -80,7 +71,8 @@ mod erc20 {
                 to: Some(caller),
                 value: initial_supply,
             });
-
+            //可以直接把日志输出到节点的日志上去
+            ink_env::debug_println!("erc2.0 contract AccountId：{:?} | total_supply: {:?}", caller, initial_supply);
         }
 
         /// Returns the total token supply.
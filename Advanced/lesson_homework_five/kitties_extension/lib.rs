@@ -0,0 +1,84 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! lesson_homework_six 的 runtime 提供的 kitties chain extension。
+//!
+//! 合约使用 [`KittiesEnvironment`] 就可以通过 `self.env().extension()` 查询和转移 kitty：
+//!
+//! ```ignore
+//! #[ink_lang::contract(env = kitties_extension::KittiesEnvironment)]
+//! mod shop {
+//!     // ...
+//!     let owner = self.env().extension().owner_of(kitty_id);
+//!     self.env().extension().transfer(kitty_id, buyer)?;
+//! }
+//! ```
+//!
+//! 函数 id 和状态码要和 runtime 的 `chain_extension.rs` 保持一致。
+
+use ink_env::{DefaultEnvironment, Environment};
+
+pub type AccountId = <DefaultEnvironment as Environment>::AccountId;
+pub type KittyIndex = u32;
+
+#[ink_lang::chain_extension]
+pub trait KittiesExtension {
+    type ErrorCode = KittiesError;
+
+    /// kitty 的所有者，不存在时返回 `None`
+    #[ink(extension = 1, handle_status = false, returns_result = false)]
+    fn owner_of(kitty_id: KittyIndex) -> Option<AccountId>;
+
+    /// kitty 的 dna，不存在时返回 `None`
+    #[ink(extension = 2, handle_status = false, returns_result = false)]
+    fn kitty_dna(kitty_id: KittyIndex) -> Option<[u8; 16]>;
+
+    /// 把合约账户拥有的 kitty 转给 `to`，`to` 需要有足够的余额质押
+    #[ink(extension = 3, returns_result = false)]
+    fn transfer(kitty_id: KittyIndex, to: AccountId);
+}
+
+/// `transfer` 失败的原因
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesError {
+    /// kitty 不存在
+    KittyNotFound,
+    /// kitty 不属于合约
+    NotOwner,
+    /// 接收者的余额不够质押
+    NotEnoughBalance,
+    /// 接收者拥有的 kitty 太多
+    TooManyKitties,
+    /// runtime 返回了未知的状态码
+    Unknown,
+}
+
+impl ink_env::chain_extension::FromStatusCode for KittiesError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            1 => Err(Self::KittyNotFound),
+            2 => Err(Self::NotOwner),
+            3 => Err(Self::NotEnoughBalance),
+            4 => Err(Self::TooManyKitties),
+            _ => Err(Self::Unknown),
+        }
+    }
+}
+
+/// 除了 chain extension 以外和默认环境相同
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesEnvironment {}
+
+impl Environment for KittiesEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = KittiesExtension;
+}
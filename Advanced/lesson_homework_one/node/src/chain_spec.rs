@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AssetsConfig, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
+	OracleModuleConfig, Signature, SudoConfig, SystemConfig, TemplateModuleConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -8,6 +8,9 @@ use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
+/// 开发链上可以支付交易费的 sufficient 资产
+const FEE_ASSET_ID: u32 = 1;
+
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
@@ -125,7 +128,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 }
 
 /// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+pub(crate) fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
@@ -149,8 +152,28 @@ fn testnet_genesis(
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: Some(root_key),
+			key: Some(root_key.clone()),
 		},
 		transaction_payment: Default::default(),
+		assets: AssetsConfig {
+			// min_balance 等于 ExistentialDeposit，交易费按 1:1 换算
+			assets: vec![(FEE_ASSET_ID, root_key.clone(), true, 500)],
+			metadata: vec![(FEE_ASSET_ID, b"Fee Token".to_vec(), b"FEE".to_vec(), 12)],
+			accounts: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| (FEE_ASSET_ID, k, 1 << 60))
+				.collect(),
+		},
+		template_module: TemplateModuleConfig {
+			// 节点启动时插入的 //Alice 链下 key 对应 root 账户
+			offchain_authorities: vec![root_key.clone()],
+		},
+		oracle_module: OracleModuleConfig {
+			// 节点启动时插入的 //Alice 报告人 key 对应 root 账户
+			reporters: vec![root_key],
+		},
+		poe_module: Default::default(),
+		kitties_module: Default::default(),
 	}
 }
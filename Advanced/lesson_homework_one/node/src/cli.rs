@@ -1,4 +1,6 @@
+use crate::service::Sealing;
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +9,31 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// How blocks are authored. `instant` and `manual` replace Aura and GRANDPA and are only
+	/// meant for development and tests.
+	#[clap(long, arg_enum, default_value = "aura")]
+	pub sealing: Sealing,
+
+	/// Secret URI of the key the kitties offchain worker signs `update_kitty` with. Defaults to
+	/// the development account of `--dev`, `--alice`, ...
+	#[clap(long)]
+	pub kitty_worker_suri: Option<String>,
+
+	/// File containing the secret URI of the kitties offchain worker key.
+	#[clap(long, parse(from_os_str), conflicts_with = "kitty-worker-suri")]
+	pub kitty_worker_key_file: Option<PathBuf>,
+}
+
+impl Cli {
+	/// The secret URI of the kitties offchain worker key given on the command line, if any.
+	pub fn kitty_worker_suri(&self) -> sc_cli::Result<Option<String>> {
+		match (&self.kitty_worker_suri, &self.kitty_worker_key_file) {
+			(Some(suri), _) => Ok(Some(suri.clone())),
+			(None, Some(path)) => Ok(Some(std::fs::read_to_string(path)?.trim().to_string())),
+			(None, None) => Ok(None),
+		}
+	}
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +77,14 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Proof of existence utilities.
+	#[clap(subcommand)]
+	Poe(crate::poe::PoeCmd),
+
+	/// Report or clear offchain storage keys written per block by the pallets.
+	OffchainKeys(crate::offchain_keys::OffchainKeysCmd),
+
+	/// Generate a raw chain spec from a TOML or JSON genesis file.
+	GenerateSpec(crate::generate_spec::GenerateSpecCmd),
 }
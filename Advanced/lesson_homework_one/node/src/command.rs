@@ -55,6 +55,15 @@ pub fn run() -> sc_cli::Result<()> {
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
+		Some(Subcommand::Poe(cmd)) => cmd.run(),
+		Some(Subcommand::GenerateSpec(cmd)) => cmd.run(),
+		Some(Subcommand::OffchainKeys(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { backend, .. } = service::new_partial(&config)?;
+				cmd.run(backend)
+			})
+		},
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
@@ -169,8 +178,11 @@ pub fn run() -> sc_cli::Result<()> {
 		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+			let sealing = cli.sealing;
+			let kitty_worker_suri = cli.kitty_worker_suri()?;
+			runner.run_node_until_exit(move |config| async move {
+				service::new_full(config, sealing, kitty_worker_suri)
+					.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
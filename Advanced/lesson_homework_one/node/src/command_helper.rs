@@ -78,41 +78,58 @@ pub fn create_benchmark_extrinsic(
 		.checked_next_power_of_two()
 		.map(|c| c / 2)
 		.unwrap_or(2) as u64;
+
+	create_extrinsic(
+		sender,
+		call,
+		nonce,
+		0,
+		genesis_hash,
+		(sp_runtime::generic::Era::mortal(period, best_block.saturated_into()), best_hash),
+		(runtime::VERSION.spec_version, runtime::VERSION.transaction_version),
+	)
+}
+
+/// Sign `call` with the runtime's `SignedExtra`.
+///
+/// `era` is the mortality together with the hash of the block it starts from (the genesis hash
+/// for an immortal transaction). `version` is the `(spec_version, transaction_version)` of the
+/// runtime the transaction is submitted to.
+pub fn create_extrinsic(
+	sender: sp_core::sr25519::Pair,
+	call: runtime::Call,
+	nonce: u32,
+	tip: runtime::Balance,
+	genesis_hash: runtime::Hash,
+	era: (sp_runtime::generic::Era, runtime::Hash),
+	version: (u32, u32),
+) -> runtime::UncheckedExtrinsic {
+	let (era, checkpoint) = era;
+	let (spec_version, transaction_version) = version;
 	let extra: runtime::SignedExtra = (
 		frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
 		frame_system::CheckSpecVersion::<runtime::Runtime>::new(),
 		frame_system::CheckTxVersion::<runtime::Runtime>::new(),
 		frame_system::CheckGenesis::<runtime::Runtime>::new(),
-		frame_system::CheckEra::<runtime::Runtime>::from(sp_runtime::generic::Era::mortal(
-			period,
-			best_block.saturated_into(),
-		)),
+		frame_system::CheckEra::<runtime::Runtime>::from(era),
 		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
 		frame_system::CheckWeight::<runtime::Runtime>::new(),
-		pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+		pallet_kitties::CheckKittyRateLimit::<runtime::Runtime>::new(),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::<runtime::Runtime>::from(tip, None),
 	);
 
 	let raw_payload = runtime::SignedPayload::from_raw(
 		call.clone(),
 		extra.clone(),
-		(
-			(),
-			runtime::VERSION.spec_version,
-			runtime::VERSION.transaction_version,
-			genesis_hash,
-			best_hash,
-			(),
-			(),
-			(),
-		),
+		((), spec_version, transaction_version, genesis_hash, checkpoint, (), (), (), ()),
 	);
 	let signature = raw_payload.using_encoded(|e| sender.sign(e));
 
 	runtime::UncheckedExtrinsic::new_signed(
-		call.clone(),
+		call,
 		sp_runtime::AccountId32::from(sender.public()).into(),
-		runtime::Signature::Sr25519(signature.clone()),
-		extra.clone(),
+		runtime::Signature::Sr25519(signature),
+		extra,
 	)
 }
 
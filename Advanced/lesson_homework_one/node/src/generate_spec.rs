@@ -0,0 +1,278 @@
+//! `generate-spec` 子命令：从创世文件生成 raw chain spec，不需要为新网络重新编译节点。
+//!
+//! ```bash
+//! node-template generate-spec ./staging.toml --output ./staging-raw.json
+//! node-template --chain ./staging-raw.json --validator
+//! ```
+//!
+//! 创世文件可以是 TOML 或 JSON（按扩展名区分），账户和公钥使用 SS58 地址，
+//! 开发用的 `//Alice` 这类 secret URI 也可以直接写。TOML 的整数放不下较大的余额，
+//! 余额也可以写成字符串。创世 kitty 的所有者要有足够的余额质押每个 kitty 的 `KittyPrice`：
+//!
+//! ```toml
+//! name = "Kitties Staging"
+//! id = "kitties_staging"
+//! chain_type = "Live"
+//! sudo = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//!
+//! [[authorities]]
+//! aura = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+//!
+//! [[endowed_accounts]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! balance = "1_000_000_000_000_000_000_000"
+//!
+//! [poe]
+//! max_claim_length = 256
+//!
+//! [[kitties]]
+//! owner = "//Alice"
+//! dna = "0x000102030405060708090a0b0c0d0e0f"
+//! ```
+
+use crate::chain_spec::{testnet_genesis, ChainSpec};
+use node_template_runtime::{
+	pallet_kitties, pallet_poe, AccountId, Balance, GenesisConfig, Runtime, WASM_BINARY,
+};
+use sc_cli::Result;
+use sc_service::ChainType;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::{bytes::from_hex, crypto::Ss58Codec, ed25519, sr25519, Get, Pair};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// 没有写 `balance` 的账户的初始余额，和开发链相同
+const DEFAULT_BALANCE: Balance = 1 << 60;
+
+/// `generate-spec`
+#[derive(Debug, clap::Parser)]
+pub struct GenerateSpecCmd {
+	/// 创世文件，`.toml` 或 `.json`
+	#[clap(parse(from_os_str))]
+	genesis: PathBuf,
+
+	/// 输出的 raw chain spec，默认打印到标准输出
+	#[clap(long, short, parse(from_os_str))]
+	output: Option<PathBuf>,
+}
+
+/// 创世文件
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenesisFile {
+	/// 链的名字
+	name: String,
+	/// 链的 id，也是默认的数据目录名
+	id: String,
+	/// `Development`、`Local` 或 `Live`
+	#[serde(default)]
+	chain_type: ChainType,
+	/// Aura 和 GRANDPA 验证人
+	authorities: Vec<AuthorityEntry>,
+	/// 初始余额
+	#[serde(default)]
+	endowed_accounts: Vec<EndowmentEntry>,
+	/// sudo 账户，也是 template 和 oracle 模块的初始链下账户
+	sudo: String,
+	#[serde(default)]
+	poe: PoeEntry,
+	/// 创世时的 kitty
+	#[serde(default)]
+	kitties: Vec<KittyEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthorityEntry {
+	/// sr25519 公钥
+	aura: String,
+	/// ed25519 公钥
+	grandpa: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndowmentEntry {
+	account: String,
+	#[serde(default, deserialize_with = "balance")]
+	balance: Option<Balance>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PoeEntry {
+	/// 新存证的最大长度，不能超过 runtime 的 `MaxClaimLength`
+	max_claim_length: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KittyEntry {
+	owner: String,
+	/// 16 字节的 dna，十六进制
+	dna: String,
+}
+
+impl GenerateSpecCmd {
+	/// 执行子命令
+	pub fn run(&self) -> Result<()> {
+		let text = fs::read_to_string(&self.genesis)?;
+		let file: GenesisFile = match self.genesis.extension().and_then(|ext| ext.to_str()) {
+			Some("toml") => {
+				toml::from_str(&text).map_err(|e| format!("Invalid genesis file: {}", e))?
+			},
+			_ => serde_json::from_str(&text).map_err(|e| format!("Invalid genesis file: {}", e))?,
+		};
+
+		let spec = file.into_chain_spec()?;
+		let json = sc_service::chain_ops::build_spec(&spec, true)?;
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+		Ok(())
+	}
+}
+
+impl GenesisFile {
+	/// 按创世文件生成 chain spec，没有写到的部分和 `testnet_genesis` 相同
+	fn into_chain_spec(self) -> std::result::Result<ChainSpec, String> {
+		let wasm_binary = WASM_BINARY.ok_or_else(|| "Wasm binary not available".to_string())?;
+
+		let authorities = self
+			.authorities
+			.iter()
+			.map(|a| {
+				Ok((
+					public::<sr25519::Pair>(&a.aura)?.into(),
+					public::<ed25519::Pair>(&a.grandpa)?.into(),
+				))
+			})
+			.collect::<std::result::Result<Vec<(AuraId, GrandpaId)>, String>>()?;
+		if authorities.is_empty() {
+			return Err("At least one authority is required".into())
+		}
+		let sudo = account(&self.sudo)?;
+		let endowments = self
+			.endowed_accounts
+			.iter()
+			.map(|e| Ok((account(&e.account)?, e.balance.unwrap_or(DEFAULT_BALANCE))))
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+		let kitties = self
+			.kitties
+			.iter()
+			.map(|k| Ok((account(&k.owner)?, dna(&k.dna)?)))
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+		let max_claim_length = self.poe.max_claim_length;
+
+		// 在 chain spec 里检查，避免生成 raw spec 时在创世构建中 panic
+		if let Some(len) = max_claim_length {
+			let max = <Runtime as pallet_poe::Config>::MaxClaimLength::get();
+			if len > max {
+				return Err(format!("poe.max_claim_length can't exceed MaxClaimLength ({})", max))
+			}
+		}
+
+		let build_genesis = move || {
+			let mut genesis = testnet_genesis(
+				wasm_binary,
+				authorities.clone(),
+				sudo.clone(),
+				endowments.iter().map(|(who, _)| who.clone()).collect(),
+				false,
+			);
+			genesis.balances.balances = endowments.clone();
+			if let Some(len) = max_claim_length {
+				genesis.poe_module.max_claim_length = len;
+			}
+			genesis.kitties_module.kitties = kitties.clone();
+			genesis
+		};
+		check_kitty_owners(&build_genesis())?;
+
+		Ok(ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			self.chain_type,
+			build_genesis,
+			vec![],
+			None,
+			None,
+			None,
+			None,
+			None,
+		))
+	}
+}
+
+/// 创世 kitty 的所有者要有足够的初始余额质押 `KittyPrice`，避免在创世构建中 panic
+fn check_kitty_owners(genesis: &GenesisConfig) -> std::result::Result<(), String> {
+	let price = <Runtime as pallet_kitties::Config>::KittyPrice::get();
+	let mut reserved = BTreeMap::<&AccountId, Balance>::new();
+	for (owner, _) in &genesis.kitties_module.kitties {
+		let amount = reserved.entry(owner).or_default();
+		*amount = amount.saturating_add(price);
+	}
+	for (owner, amount) in reserved {
+		let balance = genesis
+			.balances
+			.balances
+			.iter()
+			.find(|(who, _)| who == owner)
+			.map_or(0, |(_, balance)| *balance);
+		if balance < amount {
+			return Err(format!(
+				"Kitty owner {} must be endowed with at least KittyPrice ({}) per kitty",
+				owner, price
+			))
+		}
+	}
+	Ok(())
+}
+
+/// 余额可以写成整数或字符串，TOML 的整数是 i64，放不下较大的余额
+fn balance<'de, D: Deserializer<'de>>(
+	deserializer: D,
+) -> std::result::Result<Option<Balance>, D::Error> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Value {
+		Integer(u64),
+		String(String),
+	}
+
+	match Option::<Value>::deserialize(deserializer)? {
+		None => Ok(None),
+		Some(Value::Integer(n)) => Ok(Some(n.into())),
+		Some(Value::String(s)) => s
+			.replace('_', "")
+			.parse()
+			.map(Some)
+			.map_err(|e| D::Error::custom(format!("Invalid balance {}: {}", s, e))),
+	}
+}
+
+/// SS58 地址或 `//Alice` 这类 secret URI 对应的公钥
+fn public<P: Pair>(s: &str) -> std::result::Result<P::Public, String>
+where
+	P::Public: Ss58Codec,
+{
+	if s.starts_with("//") {
+		P::from_string(s, None)
+			.map(|pair| pair.public())
+			.map_err(|e| format!("Invalid secret URI {}: {:?}", s, e))
+	} else {
+		P::Public::from_ss58check(s).map_err(|e| format!("Invalid SS58 address {}: {:?}", s, e))
+	}
+}
+
+fn account(s: &str) -> std::result::Result<AccountId, String> {
+	public::<sr25519::Pair>(s).map(Into::into)
+}
+
+fn dna(s: &str) -> std::result::Result<[u8; 16], String> {
+	let bytes = from_hex(s).map_err(|e| format!("Invalid dna {}: {}", s, e))?;
+	bytes.try_into().map_err(|_| format!("Kitty dna must be 16 bytes: {}", s))
+}
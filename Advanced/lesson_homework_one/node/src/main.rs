@@ -7,6 +7,10 @@ mod service;
 mod cli;
 mod command;
 mod command_helper;
+mod generate_spec;
+mod metrics;
+mod offchain_keys;
+mod poe;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
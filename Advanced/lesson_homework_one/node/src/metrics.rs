@@ -0,0 +1,190 @@
+//! 自定义模块和链下工作机的 Prometheus 指标。
+//!
+//! 每导入一个最佳块，读取它的 runtime 事件统计 kitties 和存证；
+//! 链下工作机把累计的运行统计写在链下持久化存储中，同时读取并导出增量。
+
+use crate::service::{FullBackend, FullClient};
+use codec::Decode;
+use futures::StreamExt;
+use node_template_runtime::{
+	opaque::Block, pallet_kitties, pallet_oracle, pallet_poe, pallet_template, Event, Hash,
+};
+use pallet_support::stats::{stats_key, OffchainWorkerStats};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_core::{
+	hashing::twox_128,
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	storage::StorageKey,
+};
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+use substrate_prometheus_endpoint::{
+	register, CounterVec, GaugeVec, Histogram, HistogramOpts, Opts, PrometheusError, Registry, U64,
+};
+
+type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// 链下工作机的名字和链下存储中统计的前缀
+const OFFCHAIN_WORKERS: [(&str, &[u8]); 3] = [
+	("template", pallet_template::OCW_STATS_PREFIX),
+	("kitties", pallet_kitties::OCW_STATS_PREFIX),
+	("oracle", pallet_oracle::OCW_STATS_PREFIX),
+];
+
+/// 解码链下工作机写入的统计
+fn decode_stats(mut data: &[u8]) -> Option<OffchainWorkerStats> {
+	OffchainWorkerStats::decode(&mut data).ok()
+}
+
+/// 节点注册的自定义指标
+pub struct Metrics {
+	kitties: CounterVec<U64>,
+	poe_claims_per_block: Histogram,
+	ocw_runs: CounterVec<U64>,
+	ocw_duration: GaugeVec<U64>,
+	ocw_http_failures: CounterVec<U64>,
+	ocw_submitted: CounterVec<U64>,
+}
+
+impl Metrics {
+	/// 在 `registry` 中注册指标
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			kitties: register(
+				CounterVec::new(
+					Opts::new("node_template_kitties_total", "Kitties created, bred and transferred"),
+					&["event"],
+				)?,
+				registry,
+			)?,
+			poe_claims_per_block: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"node_template_poe_claims_per_block",
+						"Proof of existence claims created per imported block",
+					)
+					.buckets(vec![0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0]),
+				)?,
+				registry,
+			)?,
+			ocw_runs: register(
+				CounterVec::new(
+					Opts::new("node_template_offchain_worker_runs_total", "Offchain worker runs"),
+					&["pallet"],
+				)?,
+				registry,
+			)?,
+			ocw_duration: register(
+				GaugeVec::new(
+					Opts::new(
+						"node_template_offchain_worker_duration_ms",
+						"Duration of the latest offchain worker run in milliseconds",
+					),
+					&["pallet"],
+				)?,
+				registry,
+			)?,
+			ocw_http_failures: register(
+				CounterVec::new(
+					Opts::new(
+						"node_template_offchain_worker_http_failures_total",
+						"Failed HTTP fetches of the offchain workers",
+					),
+					&["pallet"],
+				)?,
+				registry,
+			)?,
+			ocw_submitted: register(
+				CounterVec::new(
+					Opts::new(
+						"node_template_offchain_worker_submitted_total",
+						"Transactions submitted to the pool by the offchain workers",
+					),
+					&["pallet"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	fn observe_events(&self, events: &[EventRecord]) {
+		let mut claims = 0u32;
+		for record in events {
+			match &record.event {
+				Event::KittiesModule(pallet_kitties::Event::KittyCreated(..)) =>
+					self.kitties.with_label_values(&["created"]).inc(),
+				Event::KittiesModule(pallet_kitties::Event::KittyBreed(..)) =>
+					self.kitties.with_label_values(&["bred"]).inc(),
+				Event::KittiesModule(pallet_kitties::Event::KittyTransfer(..)) =>
+					self.kitties.with_label_values(&["transferred"]).inc(),
+				Event::PoeModule(pallet_poe::Event::ClaimCreated(..)) => claims += 1,
+				_ => {},
+			}
+		}
+		self.poe_claims_per_block.observe(claims.into());
+	}
+
+	fn observe_offchain_worker(
+		&self,
+		pallet: &str,
+		last: &OffchainWorkerStats,
+		now: &OffchainWorkerStats,
+	) {
+		let labels = &[pallet];
+		self.ocw_runs.with_label_values(labels).inc_by(now.runs.saturating_sub(last.runs));
+		self.ocw_http_failures
+			.with_label_values(labels)
+			.inc_by(now.http_failures.saturating_sub(last.http_failures));
+		self.ocw_submitted
+			.with_label_values(labels)
+			.inc_by(now.submitted.saturating_sub(last.submitted));
+		if now.runs != last.runs {
+			self.ocw_duration.with_label_values(labels).set(now.last_duration_ms);
+		}
+	}
+}
+
+/// 区块 `hash` 的 `System::Events`
+fn block_events(client: &FullClient, hash: Hash) -> sp_blockchain::Result<Vec<EventRecord>> {
+	let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+	let events = match client.storage(&BlockId::<Block>::Hash(hash), &key)? {
+		Some(data) => Vec::<EventRecord>::decode(&mut &data.0[..])
+			.map_err(|e| sp_blockchain::Error::Backend(format!("Invalid events: {}", e)))?,
+		None => Vec::new(),
+	};
+	Ok(events)
+}
+
+/// 跟随区块导入更新指标，作为节点的后台任务运行
+pub async fn run(client: Arc<FullClient>, backend: Arc<FullBackend>, metrics: Metrics) {
+	let offchain = backend.offchain_storage();
+	let read_stats = |prefix: &[u8]| {
+		offchain
+			.as_ref()
+			.and_then(|storage| storage.get(STORAGE_PREFIX, &stats_key(prefix)))
+			.and_then(|data| decode_stats(&data))
+			.unwrap_or_default()
+	};
+
+	// 节点启动前的统计不计入
+	let mut last = OFFCHAIN_WORKERS.map(|(_, prefix)| read_stats(prefix));
+
+	let mut imports = client.import_notification_stream();
+	while let Some(notification) = imports.next().await {
+		// 分叉上的块不统计，避免重复计数
+		if !notification.is_new_best {
+			continue
+		}
+
+		match block_events(&client, notification.hash) {
+			Ok(events) => metrics.observe_events(&events),
+			Err(e) => log::warn!("Failed to read events of {}: {}", notification.hash, e),
+		}
+
+		for ((pallet, prefix), last) in OFFCHAIN_WORKERS.iter().zip(last.iter_mut()) {
+			let now = read_stats(prefix);
+			metrics.observe_offchain_worker(pallet, last, &now);
+			*last = now;
+		}
+	}
+}
@@ -0,0 +1,98 @@
+//! `offchain-keys` 子命令：统计并清理 `pallet_template::derive_key` 和
+//! `pallet_kitties::derived_key` 按块写入的链下存储。
+//!
+//! ```bash
+//! node-template offchain-keys --dev
+//! # 删除，只保留最近 100 个块的 key
+//! node-template offchain-keys --dev --clear --keep 100
+//! ```
+//!
+//! 数据库同时只能被一个进程打开，需要先停止节点。
+
+use codec::Encode;
+use node_template_runtime::{pallet_kitties, pallet_template, Block, BlockNumber};
+use sc_cli::{CliConfiguration, DatabaseParams, Result, SharedParams};
+use sc_client_api::Backend;
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use std::sync::Arc;
+
+/// 按块写入链下存储的 key 前缀，后面跟 SCALE 编码的块号
+const PREFIXES: [(&str, &[u8]); 2] = [
+	("pallet_template::derive_key", pallet_template::DERIVED_KEY_PREFIX),
+	("pallet_kitties::derived_key", pallet_kitties::INDEXING_KEY_PREFIX),
+];
+
+/// `offchain-keys`
+#[derive(Debug, clap::Parser)]
+pub struct OffchainKeysCmd {
+	/// 删除找到的 key
+	#[clap(long)]
+	clear: bool,
+
+	/// 删除时保留最近多少个块的 key
+	#[clap(long, default_value = "0")]
+	keep: BlockNumber,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl OffchainKeysCmd {
+	/// 执行子命令
+	pub fn run<B: Backend<Block>>(&self, backend: Arc<B>) -> Result<()> {
+		let mut storage =
+			backend.offchain_storage().ok_or("Offchain storage is not available")?;
+		let best = backend.blockchain().info().best_number;
+		let keep_from = best.saturating_sub(self.keep);
+
+		for (name, prefix) in PREFIXES {
+			let (mut count, mut bytes, mut cleared) = (0u32, 0usize, 0u32);
+			let mut range: Option<(BlockNumber, BlockNumber)> = None;
+
+			// 链下工作机处理的块可能比数据库里的最新块新一个
+			for n in 0..=best.saturating_add(1) {
+				let key = [prefix, &n.encode()[..]].concat();
+				let value = match storage.get(STORAGE_PREFIX, &key) {
+					Some(value) => value,
+					None => continue,
+				};
+
+				count += 1;
+				bytes += key.len() + value.len();
+				range = Some(range.map_or((n, n), |(first, _)| (first, n)));
+				if self.clear && n < keep_from {
+					storage.remove(STORAGE_PREFIX, &key);
+					cleared += 1;
+				}
+			}
+
+			match range {
+				Some((first, last)) => println!(
+					"{}: {} keys, {} bytes, blocks #{}..=#{}",
+					name, count, bytes, first, last
+				),
+				None => println!("{}: no keys", name),
+			}
+			if self.clear {
+				println!("{}: cleared {} keys", name, cleared);
+			}
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for OffchainKeysCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -0,0 +1,263 @@
+//! `poe` 子命令：计算文件存证、离线签名 `PoeModule::create_claim` 交易以及查询存证。
+//!
+//! ```bash
+//! node-template poe hash ./contract.pdf
+//! # 连接节点，签名并提交
+//! node-template poe create-claim ./contract.pdf --suri //Alice --url http://localhost:9933
+//! # 离线签名，只打印编码后的交易，由联网的机器用 author_submitExtrinsic 提交
+//! node-template poe create-claim ./contract.pdf --suri "<secret phrase>" \
+//!     --nonce 3 --genesis-hash 0x... --print
+//! node-template poe verify ./contract.pdf --url http://localhost:9933
+//! ```
+
+use crate::command_helper::create_extrinsic;
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::client::ClientT,
+	http_client::{HttpClient, HttpClientBuilder},
+	rpc_params,
+};
+use node_template_runtime::{
+	self as runtime, pallet_poe, AccountId, Balance, BlockNumber, Hash, Header, Index,
+};
+use poe_verifier::{storage_key, verify_receipt, ClaimReceipt, TrustedSet};
+use sc_cli::{Result, RuntimeVersion};
+use sp_core::{
+	crypto::Ss58Codec, hashing::blake2_256, hexdisplay::HexDisplay, sr25519, storage::StorageKey,
+	Bytes, Pair,
+};
+use sp_runtime::{generic::Era, traits::Header as HeaderT, SaturatedConversion};
+use std::{
+	future::Future,
+	path::{Path, PathBuf},
+};
+
+/// `Proofs` 中存储的值：所有者，区块数和时间戳
+type ProofOf = (AccountId, BlockNumber, pallet_poe::MomentOf<runtime::Runtime>);
+
+/// 存证相关的工具
+#[derive(Debug, clap::Subcommand)]
+pub enum PoeCmd {
+	/// 打印文件的存证（blake2-256 hash）
+	Hash(HashCmd),
+
+	/// 签名（并提交）一个 `PoeModule::create_claim` 交易
+	CreateClaim(CreateClaimCmd),
+
+	/// 查询文件的存证是否在链上
+	Verify(VerifyCmd),
+}
+
+/// `poe hash`
+#[derive(Debug, clap::Parser)]
+pub struct HashCmd {
+	/// 需要存证的文件
+	#[clap(parse(from_os_str))]
+	file: PathBuf,
+}
+
+/// `poe create-claim`
+#[derive(Debug, clap::Parser)]
+pub struct CreateClaimCmd {
+	/// 需要存证的文件
+	#[clap(parse(from_os_str))]
+	file: PathBuf,
+
+	/// 签名账户的 secret URI，比如 `//Alice` 或助记词
+	#[clap(long)]
+	suri: String,
+
+	/// secret URI 的密码
+	#[clap(long)]
+	password: Option<String>,
+
+	/// 节点的 HTTP RPC 地址，用来查询 nonce、创世块和 runtime 版本
+	#[clap(long)]
+	url: Option<String>,
+
+	/// 账户 nonce，离线签名时必填
+	#[clap(long)]
+	nonce: Option<Index>,
+
+	/// 创世块哈希，离线签名时必填
+	#[clap(long)]
+	genesis_hash: Option<Hash>,
+
+	/// 小费
+	#[clap(long, default_value = "0")]
+	tip: Balance,
+
+	/// 只打印编码后的交易，不提交
+	#[clap(long)]
+	print: bool,
+}
+
+/// `poe verify`
+#[derive(Debug, clap::Parser)]
+pub struct VerifyCmd {
+	/// 需要查询的文件
+	#[clap(parse(from_os_str))]
+	file: PathBuf,
+
+	/// 节点的 HTTP RPC 地址
+	#[clap(long, default_value = "http://localhost:9933")]
+	url: String,
+
+	/// 审计方信任的创世块哈希与 GRANDPA 权威集合（见 `poe-verifier`），
+	/// 提供时通过 `poe_getClaimReceipt` 校验存证，而不信任节点
+	#[clap(long, parse(from_os_str))]
+	trusted: Option<PathBuf>,
+}
+
+impl PoeCmd {
+	/// 执行子命令
+	pub fn run(&self) -> Result<()> {
+		match self {
+			PoeCmd::Hash(cmd) => cmd.run(),
+			PoeCmd::CreateClaim(cmd) => block_on(cmd.run()),
+			PoeCmd::Verify(cmd) => block_on(cmd.run()),
+		}
+	}
+}
+
+impl HashCmd {
+	fn run(&self) -> Result<()> {
+		println!("0x{}", HexDisplay::from(&claim_of(&self.file)?));
+		Ok(())
+	}
+}
+
+impl CreateClaimCmd {
+	async fn run(&self) -> Result<()> {
+		let claim = claim_of(&self.file)?.to_vec();
+		let pair = sr25519::Pair::from_string(&self.suri, self.password.as_deref())
+			.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+		let account = AccountId::from(pair.public());
+
+		let client = self.url.as_deref().map(rpc_client).transpose()?;
+		let nonce = match (self.nonce, &client) {
+			(Some(nonce), _) => nonce,
+			(None, Some(client)) =>
+				request(client, "system_accountNextIndex", rpc_params![account.to_ss58check()])
+					.await?,
+			(None, None) => return Err("`--nonce` is required without `--url`".into()),
+		};
+		let genesis_hash = match (self.genesis_hash, &client) {
+			(Some(hash), _) => hash,
+			(None, Some(client)) =>
+				request::<Option<Hash>>(client, "chain_getBlockHash", rpc_params![0])
+					.await?
+					.ok_or("Node has no genesis block")?,
+			(None, None) => return Err("`--genesis-hash` is required without `--url`".into()),
+		};
+
+		// 联网时使用节点的 runtime 版本和最新块；离线时使用本地 runtime 版本，交易永不过期
+		let (version, era) = match &client {
+			Some(client) => {
+				let version: RuntimeVersion =
+					request(client, "state_getRuntimeVersion", rpc_params![]).await?;
+				let best: Header = request(client, "chain_getHeader", rpc_params![]).await?;
+				let period = runtime::BlockHashCount::get()
+					.checked_next_power_of_two()
+					.map(|c| c / 2)
+					.unwrap_or(2) as u64;
+				(
+					(version.spec_version, version.transaction_version),
+					(Era::mortal(period, best.number().saturated_into()), best.hash()),
+				)
+			},
+			None => (
+				(runtime::VERSION.spec_version, runtime::VERSION.transaction_version),
+				(Era::Immortal, genesis_hash),
+			),
+		};
+
+		let call = pallet_poe::Call::<runtime::Runtime>::create_claim { claim: claim.clone() };
+		let extrinsic =
+			create_extrinsic(pair, call.into(), nonce, self.tip, genesis_hash, era, version);
+		let encoded = Bytes(extrinsic.encode());
+
+		println!("claim:     0x{}", HexDisplay::from(&claim));
+		println!("signer:    {}", account.to_ss58check());
+		match client {
+			Some(client) if !self.print => {
+				let hash: Hash =
+					request(&client, "author_submitExtrinsic", rpc_params![encoded]).await?;
+				println!("submitted: {:?}", hash);
+			},
+			_ => println!("extrinsic: 0x{}", HexDisplay::from(&encoded.0)),
+		}
+		Ok(())
+	}
+}
+
+impl VerifyCmd {
+	async fn run(&self) -> Result<()> {
+		let claim = claim_of(&self.file)?.to_vec();
+		let client = rpc_client(&self.url)?;
+		println!("claim: 0x{}", HexDisplay::from(&claim));
+
+		let proof = match &self.trusted {
+			Some(trusted) => {
+				let trusted: TrustedSet = serde_json::from_slice(
+					&std::fs::read(trusted).map_err(|e| format!("{}: {}", trusted.display(), e))?,
+				)
+				.map_err(|e| format!("{}: {}", trusted.display(), e))?;
+				let receipt: ClaimReceipt =
+					request(&client, "poe_getClaimReceipt", rpc_params![Bytes(claim)]).await?;
+				let verified = verify_receipt(&receipt, &trusted).map_err(|e| e.to_string())?;
+				println!("block: #{} ({:?}), finalized", verified.block_number, verified.block_hash);
+				verified.decode_value::<ProofOf>().map_err(|e| e.to_string())?
+			},
+			None => {
+				let value: Option<Bytes> = request(
+					&client,
+					"state_getStorage",
+					rpc_params![StorageKey(storage_key(&claim))],
+				)
+				.await?;
+				value
+					.map(|v| ProofOf::decode(&mut &v.0[..]))
+					.transpose()
+					.map_err(|e| format!("Invalid `Proofs` value: {}", e))?
+			},
+		};
+
+		match proof {
+			Some((owner, block_number, moment)) => {
+				println!("owner: {}", owner.to_ss58check());
+				println!("at:    block #{}, timestamp {} ms", block_number, moment);
+				Ok(())
+			},
+			None => Err("Claim does not exist".into()),
+		}
+	}
+}
+
+/// 文件的存证：内容的 blake2-256 hash
+fn claim_of(file: &Path) -> Result<[u8; 32]> {
+	let content = std::fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+	Ok(blake2_256(&content))
+}
+
+fn rpc_client(url: &str) -> Result<HttpClient> {
+	HttpClientBuilder::default()
+		.build(url)
+		.map_err(|e| format!("Invalid RPC url {}: {}", url, e).into())
+}
+
+async fn request<R: serde::de::DeserializeOwned>(
+	client: &HttpClient,
+	method: &str,
+	params: Option<jsonrpsee::types::ParamsSer<'_>>,
+) -> Result<R> {
+	client.request(method, params).await.map_err(|e| format!("{}: {}", method, e).into())
+}
+
+fn block_on<F: Future<Output = Result<()>>>(future: F) -> Result<()> {
+	tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.map_err(|e| format!("Unable to start tokio runtime: {}", e))?
+		.block_on(future)
+}
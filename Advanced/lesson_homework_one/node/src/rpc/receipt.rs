@@ -0,0 +1,121 @@
+//! 导出存证的自包含凭证，供审计方用 `poe-verifier` 离线校验。
+
+use std::sync::Arc;
+
+use codec::Decode;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{
+	opaque::{Block, Header},
+	BlockNumber, Hash,
+};
+use poe_verifier::{storage_key, ClaimReceipt};
+use sc_client_api::{ProofProvider, StorageProvider};
+use sc_finality_grandpa::{FinalityProof, FinalityProofProvider, SharedAuthoritySet};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::generic::BlockId;
+
+#[rpc(client, server)]
+pub trait PoeReceiptApi {
+	/// 返回存证 `claim` 在最新 GRANDPA 最终确认块上的凭证
+	#[method(name = "poe_getClaimReceipt")]
+	fn claim_receipt(&self, claim: Bytes) -> RpcResult<ClaimReceipt>;
+}
+
+/// 凭证 RPC 的错误码
+pub enum Error {
+	/// 还没有可用的 GRANDPA justification
+	NoJustification,
+	/// 读取链上数据失败
+	ClientError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::NoJustification => 1,
+			Error::ClientError => 2,
+		}
+	}
+}
+
+fn rpc_error(code: Error, message: &str, data: impl ToString) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(code.into(), message, Some(data.to_string()))).into()
+}
+
+/// `poe_getClaimReceipt` 的实现
+pub struct PoeReceipt<C, B> {
+	client: Arc<C>,
+	finality_provider: Arc<FinalityProofProvider<B, Block>>,
+	shared_authority_set: SharedAuthoritySet<Hash, BlockNumber>,
+}
+
+impl<C, B> PoeReceipt<C, B> {
+	/// 创建凭证 RPC
+	pub fn new(
+		client: Arc<C>,
+		finality_provider: Arc<FinalityProofProvider<B, Block>>,
+		shared_authority_set: SharedAuthoritySet<Hash, BlockNumber>,
+	) -> Self {
+		Self { client, finality_provider, shared_authority_set }
+	}
+}
+
+impl<C, B> PoeReceiptApiServer for PoeReceipt<C, B>
+where
+	C: HeaderBackend<Block>
+		+ ProofProvider<Block>
+		+ StorageProvider<Block, B>
+		+ Send
+		+ Sync
+		+ 'static,
+	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
+{
+	fn claim_receipt(&self, claim: Bytes) -> RpcResult<ClaimReceipt> {
+		let info = self.client.info();
+
+		// 取最新的 justification，凭证锚定在它最终确认的块上
+		let encoded = self
+			.finality_provider
+			.prove_finality(info.finalized_number)
+			.map_err(|e| rpc_error(Error::ClientError, "Unable to prove finality.", e))?
+			.ok_or_else(|| {
+				rpc_error(Error::NoJustification, "No GRANDPA justification available yet.", "")
+			})?;
+		let FinalityProof { block, justification, .. } =
+			FinalityProof::<Header>::decode(&mut &encoded[..])
+				.map_err(|e| rpc_error(Error::ClientError, "Invalid finality proof.", e))?;
+
+		let at = BlockId::Hash(block);
+		let header = self
+			.client
+			.header(at)
+			.map_err(|e| rpc_error(Error::ClientError, "Unable to read header.", e))?
+			.ok_or_else(|| rpc_error(Error::ClientError, "Finalized header not found.", block))?;
+
+		let key = storage_key(&claim);
+		let value = self
+			.client
+			.storage(&at, &StorageKey(key.clone()))
+			.map_err(|e| rpc_error(Error::ClientError, "Unable to read storage.", e))?
+			.map(|data| Bytes(data.0));
+		let proof = self
+			.client
+			.read_proof(&at, &mut std::iter::once(key.as_slice()))
+			.map_err(|e| rpc_error(Error::ClientError, "Unable to build read proof.", e))?;
+
+		Ok(ClaimReceipt {
+			claim,
+			value,
+			proof: proof.into_nodes().into_iter().map(Bytes).collect(),
+			header,
+			justification: justification.into(),
+			genesis_hash: info.genesis_hash,
+			set_id: self.shared_authority_set.set_id(),
+		})
+	}
+}
@@ -1,14 +1,22 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use futures::{channel::mpsc, StreamExt};
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_client_api::{BlockBackend, ExecutorProvider};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::{aura::AuraConsensusDataProvider, timestamp::SlotTimestampProvider},
+	EngineCommand, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
 use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_transaction_pool_api::TransactionPool;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_core::crypto::Ss58Codec;
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
 use std::{sync::Arc, time::Duration};
 
 // Our native executor instance.
@@ -31,33 +39,44 @@ impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 	}
 }
 
+/// Block authoring mode of the node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ArgEnum)]
+pub enum Sealing {
+	/// Author blocks in Aura slots and finalize them with GRANDPA.
+	Aura,
+	/// Author and finalize a block as soon as a transaction enters the pool.
+	Instant,
+	/// Author blocks only when requested through the `engine_createBlock` RPC.
+	Manual,
+}
+
 pub(crate) type FullClient =
 sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+pub(crate) type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
-pub fn new_partial(
+type Service = sc_service::PartialComponents<
+	FullClient,
+	FullBackend,
+	FullSelectChain,
+	sc_consensus::DefaultImportQueue<Block, FullClient>,
+	sc_transaction_pool::FullPool<Block, FullClient>,
+	(
+		sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
+		sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+		Option<Telemetry>,
+	),
+>;
+
+pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
+	new_partial_with_sealing(config, Sealing::Aura)
+}
+
+/// 按出块方式选择导入队列：manual seal 出的块没有 Aura 签名，Aura 导入队列会拒绝这些块
+pub fn new_partial_with_sealing(
 	config: &Configuration,
-) -> Result<
-	sc_service::PartialComponents<
-		FullClient,
-		FullBackend,
-		FullSelectChain,
-		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
-		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
-			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
-			Option<Telemetry>,
-		),
-	>,
-	ServiceError,
-> {
+	sealing: Sealing,
+) -> Result<Service, ServiceError> {
 	if config.keystore_remote.is_some() {
 		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
 	}
@@ -90,11 +109,24 @@ pub fn new_partial(
 
 	if config.offchain_worker.enabled {
 		let keystore = keystore_container.sync_keystore();
-		sp_keystore::SyncCryptoStore::sr25519_generate_new(
-			&*keystore,
-			node_template_runtime::pallet_kitties::KEY_TYPE,
-			Some("//Alice"),
-		).expect("Creating key with account Alice should succeed.");
+		// 只给 `--dev`、`--alice` 等开发账户自动插入 template 和 oracle 链下工作机的 key，
+		// 其他节点通过 `author_insertKey` 插入自己的 key，避免所有节点共用公开的开发 key，
+		// 也避免所有节点作为同一个 oracle 报告者
+		if let Some(seed) = &config.dev_key_seed {
+			for (name, key_type) in [
+				("template", node_template_runtime::pallet_template::KEY_TYPE),
+				("oracle", node_template_runtime::pallet_oracle::KEY_TYPE),
+			] {
+				sp_keystore::SyncCryptoStore::sr25519_generate_new(
+					&*keystore,
+					key_type,
+					Some(seed),
+				)
+				.map_err(|e| {
+					ServiceError::Other(format!("Invalid {} offchain worker key: {}", name, e))
+				})?;
+			}
+		}
 	}
 
 	let telemetry = telemetry.map(|(worker, telemetry)| {
@@ -119,32 +151,40 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+	let import_queue = match sealing {
+		Sealing::Aura => {
+			let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-	let import_queue =
-		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
-			justification_import: Some(Box::new(grandpa_block_import.clone())),
-			client: client.clone(),
-			create_inherent_data_providers: move |_, ()| async move {
-				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+			sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
+				block_import: grandpa_block_import.clone(),
+				justification_import: Some(Box::new(grandpa_block_import.clone())),
+				client: client.clone(),
+				create_inherent_data_providers: move |_, ()| async move {
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
-				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-						*timestamp,
-						slot_duration,
-					);
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+							*timestamp,
+							slot_duration,
+						);
 
-				Ok((timestamp, slot))
-			},
-			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
-				client.executor().clone(),
-			),
-			registry: config.prometheus_registry(),
-			check_for_equivocation: Default::default(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+					Ok((timestamp, slot))
+				},
+				spawner: &task_manager.spawn_essential_handle(),
+				can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
+					client.executor().clone(),
+				),
+				registry: config.prometheus_registry(),
+				check_for_equivocation: Default::default(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
+			})?
+		},
+		Sealing::Instant | Sealing::Manual => sc_consensus_manual_seal::import_queue(
+			Box::new(grandpa_block_import.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		),
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -165,8 +205,43 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
+/// 插入 kitties 链下工作机的 "kty!" key。
+/// 来源依次是 `--kitty-worker-suri`（或 `--kitty-worker-key-file`）和 `--dev`、`--alice` 等开发账户；
+/// 都没有时使用 keystore 里已有的 key，比如通过 `author_insertKey` 插入的
+fn provision_kitty_worker_key(
+	config: &Configuration,
+	keystore: SyncCryptoStorePtr,
+	suri: Option<String>,
+) -> Result<(), ServiceError> {
+	if !config.offchain_worker.enabled {
+		return Ok(())
+	}
+
+	let key_type = node_template_runtime::pallet_kitties::KEY_TYPE;
+	if let Some(suri) = suri.or_else(|| config.dev_key_seed.clone()) {
+		let public = SyncCryptoStore::sr25519_generate_new(&*keystore, key_type, Some(&suri))
+			.map_err(|e| ServiceError::Other(format!("Invalid kitties offchain worker key: {}", e)))?;
+		log::info!("🐱 Kitties offchain worker key: {}", public.to_ss58check());
+	} else if SyncCryptoStore::sr25519_public_keys(&*keystore, key_type).is_empty() {
+		log::warn!("⚠️  The offchain worker is enabled but there is no kitties \"kty!\" key.");
+		log::warn!(
+			"⚠️  Every `update_kitty` submission will fail with \"No local accounts available\"."
+		);
+		log::warn!(
+			"⚠️  Start the node with `--kitty-worker-suri`, `--kitty-worker-key-file` or `--alice`, \
+			 or insert a key with the `author_insertKey` RPC."
+		);
+	}
+
+	Ok(())
+}
+
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Sealing,
+	kitty_worker_suri: Option<String>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -176,7 +251,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial_with_sealing(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
 		match remote_keystore(url) {
@@ -188,6 +263,8 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 				))),
 		};
 	}
+	provision_kitty_worker_key(&config, keystore_container.sync_keystore(), kitty_worker_suri)?;
+
 	let grandpa_protocol_name = sc_finality_grandpa::protocol_standard_name(
 		&client.block_hash(0).ok().flatten().expect("Genesis block exists; qed"),
 		&config.chain_spec,
@@ -227,20 +304,54 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
-	let enable_grandpa = !config.disable_grandpa;
+	// manual seal 自己确认区块，不启动 GRANDPA
+	let enable_grandpa = !config.disable_grandpa && sealing == Sealing::Aura;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	// `engine_createBlock` 和 `engine_finalizeBlock` 发送的出块命令
+	let (command_sink, manual_commands) = match sealing {
+		Sealing::Manual => {
+			let (sink, stream) = mpsc::channel(1024);
+			(Some(sink), Some(stream))
+		},
+		Sealing::Aura | Sealing::Instant => (None, None),
+	};
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let shared_authority_set = grandpa_link.shared_authority_set().clone();
+		let finality_provider = sc_finality_grandpa::FinalityProofProvider::new_for_service(
+			backend.clone(),
+			Some(shared_authority_set.clone()),
+		);
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				grandpa: crate::rpc::GrandpaDeps {
+					shared_authority_set: shared_authority_set.clone(),
+					finality_provider: finality_provider.clone(),
+				},
+				command_sink: command_sink.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
 
+	// 自定义模块和链下工作机的指标，只有开启 Prometheus 时才统计
+	if let Some(registry) = prometheus_registry.as_ref() {
+		let metrics = crate::metrics::Metrics::register(registry)
+			.map_err(|e| ServiceError::Other(e.to_string()))?;
+		task_manager.spawn_handle().spawn(
+			"pallet-metrics",
+			None,
+			crate::metrics::run(client.clone(), backend.clone(), metrics),
+		);
+	}
+
 	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		network: network.clone(),
 		client: client.clone(),
@@ -258,51 +369,102 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
-			transaction_pool,
+			transaction_pool.clone(),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|x| x.handle()),
 		);
 
-		let can_author_with =
-			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
-
-		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
-
-		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
-			StartAuraParams {
-				slot_duration,
-				client,
-				select_chain,
-				block_import,
-				proposer_factory,
-				create_inherent_data_providers: move |_, ()| async move {
-					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+		match sealing {
+			Sealing::Aura => {
+				let can_author_with =
+					sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
-							*timestamp,
-							slot_duration,
-						);
+				let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-					Ok((timestamp, slot))
-				},
-				force_authoring,
-				backoff_authoring_blocks,
-				keystore: keystore_container.sync_keystore(),
-				can_author_with,
-				sync_oracle: network.clone(),
-				justification_sync_link: network.clone(),
-				block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
-				max_block_proposal_slot_portion: None,
-				telemetry: telemetry.as_ref().map(|x| x.handle()),
+				let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
+					StartAuraParams {
+						slot_duration,
+						client,
+						select_chain,
+						block_import,
+						proposer_factory,
+						create_inherent_data_providers: move |_, ()| async move {
+							let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+							let slot =
+								sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+									*timestamp,
+									slot_duration,
+								);
+
+							Ok((timestamp, slot))
+						},
+						force_authoring,
+						backoff_authoring_blocks,
+						keystore: keystore_container.sync_keystore(),
+						can_author_with,
+						sync_oracle: network.clone(),
+						justification_sync_link: network.clone(),
+						block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
+						max_block_proposal_slot_portion: None,
+						telemetry: telemetry.as_ref().map(|x| x.handle()),
+					},
+				)?;
+
+				// the AURA authoring task is considered essential, i.e. if it
+				// fails we take down the service with it.
+				task_manager
+					.spawn_essential_handle()
+					.spawn_blocking("aura", Some("block-authoring"), aura);
 			},
-		)?;
-
-		// the AURA authoring task is considered essential, i.e. if it
-		// fails we take down the service with it.
-		task_manager
-			.spawn_essential_handle()
-			.spawn_blocking("aura", Some("block-authoring"), aura);
+			Sealing::Instant | Sealing::Manual => {
+				// instant 模式下每个进入交易池的交易都立即出块并最终确认
+				let commands_stream = match manual_commands {
+					Some(stream) => stream.boxed(),
+					None => transaction_pool
+						.import_notification_stream()
+						.map(|_| EngineCommand::SealNewBlock {
+							create_empty: false,
+							finalize: true,
+							parent_hash: None,
+							sender: None,
+						})
+						.boxed(),
+				};
+
+				// runtime 仍然使用 Aura：区块需要带 Aura slot 的 digest，
+				// 时间戳每个块前进一个 slot，满足 `pallet_timestamp` 的 MinimumPeriod
+				let client_for_inherents = client.clone();
+				let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+					block_import,
+					env: proposer_factory,
+					client: client.clone(),
+					pool: transaction_pool,
+					commands_stream,
+					select_chain,
+					consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(
+						client.clone(),
+					))),
+					create_inherent_data_providers: move |_, ()| {
+						let client = client_for_inherents.clone();
+						async move {
+							let timestamp = SlotTimestampProvider::new_aura(client)
+								.map_err(|e| format!("{:?}", e))?;
+							let slot =
+								sp_consensus_aura::inherents::InherentDataProvider::new(timestamp.slot());
+
+							Ok((timestamp, slot))
+						}
+					},
+				});
+
+				task_manager.spawn_essential_handle().spawn_blocking(
+					"manual-seal",
+					Some("block-authoring"),
+					manual_seal,
+				);
+			},
+		}
 	}
 
 	if enable_grandpa {
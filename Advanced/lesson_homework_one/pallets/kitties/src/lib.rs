@@ -11,34 +11,26 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+mod rate_limit;
+pub use rate_limit::{CheckKittyRateLimit, RATE_LIMITED};
+
 
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"kty!");
 
-pub mod crypto {
-	use super::KEY_TYPE;
-	use sr25519::Signature as Sr25519Signature;
-	use sp_runtime::{
-		app_crypto::{app_crypto, sr25519},
-		traits::Verify,
-		MultiSignature, MultiSigner,
-	};
-	app_crypto!(sr25519, KEY_TYPE);
+/// `derived_key` 写入的链下索引 key 的前缀，后面跟 SCALE 编码的块号
+pub const INDEXING_KEY_PREFIX: &[u8] = b"kitty_pallet::indexing/";
 
-	pub struct KittiesAuthId;
+/// 链下存储中记录链下索引已经清理到的块
+const PRUNE_CURSOR_KEY: &[u8] = b"kitty_pallet::prune-cursor";
 
-	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for KittiesAuthId {
-		type RuntimeAppPublic = Public;
-		type GenericSignature = sr25519::Signature;
-		type GenericPublic = sr25519::Public;
-	}
+/// 链下工作机运行统计的前缀，统计由 `pallet_support::stats` 保存在链下存储中
+pub const OCW_STATS_PREFIX: &[u8] = b"kitty_pallet";
 
-	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
-	for KittiesAuthId
-	{
-		type RuntimeAppPublic = Public;
-		type GenericSignature = sr25519::Signature;
-		type GenericPublic = sr25519::Public;
-	}
+/// `on_idle` 每个块最多清理多少个账户的创建记录
+const MAX_CREATIONS_PRUNED_PER_BLOCK: u32 = 64;
+
+pub mod crypto {
+	pallet_support::offchain_crypto!(super::KEY_TYPE, KittiesAuthId);
 }
 
 #[frame_support::pallet]
@@ -51,7 +43,15 @@ pub mod pallet {
 	use frame_system::pallet_prelude::*;
 	use sp_io::offchain_index;
 	use sp_runtime::offchain::storage::StorageValueRef;
-	use sp_runtime::traits::Zero;
+	use sp_runtime::traits::{One, Saturating, Zero};
+	use pallet_support::{
+		events::IndexedEvents,
+		prune::{block_key, prune_block_keys},
+		stats::update_stats,
+	};
+	use super::{
+		INDEXING_KEY_PREFIX, MAX_CREATIONS_PRUNED_PER_BLOCK, OCW_STATS_PREFIX, PRUNE_CURSOR_KEY,
+	};
 	use sp_runtime::traits::{AtLeast32Bit, Bounded, CheckedAdd};
 
 
@@ -99,6 +99,18 @@ pub mod pallet {
 		type MaxKittyIndex: Get<u32>;
 
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// 链下索引保留多少个块，更早的由链下工作机清理
+		#[pallet::constant]
+		type OffchainKeyRetention: Get<Self::BlockNumber>;
+
+		/// 滑动窗口内每个账户最多发起多少次 `create` 和 `breed`
+		#[pallet::constant]
+		type MaxCreationsPerWindow: Get<u32>;
+
+		/// [`CheckKittyRateLimit`] 的滑动窗口，单位是块
+		#[pallet::constant]
+		type CreationWindow: Get<Self::BlockNumber>;
 	}
 
 
@@ -125,10 +137,52 @@ pub mod pallet {
 	#[pallet::getter(fn owner_kitties)]
 	pub type OwnerKitties<T:Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<T::KittyIndex, T::MaxKittyIndex>, ValueQuery>;
 
+	/// 账户在滑动窗口内发起 `create` 和 `breed` 的块号，由 [`CheckKittyRateLimit`] 维护
+	#[pallet::storage]
+	pub type RecentCreations<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<T::BlockNumber, T::MaxCreationsPerWindow>,
+		ValueQuery,
+	>;
+
+	/// `on_idle` 清理 `RecentCreations` 时上次停下的 key
+	#[pallet::storage]
+	pub type RecentCreationsCursor<T: Config> = StorageValue<_, Vec<u8>>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// 创世时的 kitty：所有者和 dna，id 从 0 开始依次分配，所有者质押 `KittyPrice`
+		pub kitties: Vec<(T::AccountId, [u8; 16])>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { kitties: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, dna) in &self.kitties {
+				let kitty_id = Pallet::<T>::get_next_id().expect("too many genesis kitties");
+				T::Currency::reserve(owner, T::KittyPrice::get())
+					.expect("genesis kitty owner can't reserve KittyPrice");
+
+				Kitties::<T>::insert(kitty_id, Kitty { dna: *dna, algebra: 1 });
+				KittyOwner::<T>::insert(kitty_id, owner);
+				OwnerKitties::<T>::try_mutate(owner, |kitties| kitties.try_push(kitty_id))
+					.expect("genesis kitty owner owns too many kitties");
+				NextKittyId::<T>::set(kitty_id + One::one());
+			}
+		}
+	}
+
 	#[pallet::event]
-	//generate_deposit 生成了一个帮助方法 deposit_event
-	//deposit_event 方便调用生成事件的宏
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	//事件通过 deposit_indexed_event 发出，kitty id 和账户会作为 topic 写入 EventTopics
 	pub enum Event<T: Config> {
 		KittyCreated(T::AccountId, T::KittyIndex, Kitty),
 		KittyBreed(T::AccountId, T::KittyIndex, Kitty),
@@ -147,14 +201,18 @@ pub mod pallet {
 		InvalidKittyId,
 	}
 
-	const UNCHAIN_TX_KEY: &[u8] = b"kitty_pallet::indexing";
 	#[derive(Debug, Encode, Decode, Default)]
 	struct IndexingData<T: Config>(T::KittyIndex);
 
 	#[pallet::hooks]
 	//定义保留函数
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::prune_recent_creations(remaining_weight)
+		}
+
 		fn offchain_worker(block_number: T::BlockNumber) {
+			let started = sp_io::offchain::timestamp();
 			let key = Self::derived_key(block_number);
 			log::info!("kitty_id block_number :{:?}, key is {:?}", block_number, key);
 			let storage_ref = StorageValueRef::persistent(&key);
@@ -173,13 +231,31 @@ pub mod pallet {
 					let _ = Self::send_signed_tx(kitty_id, 3);
 				}
 			}
+
+			Self::prune_indexing_keys(block_number);
+
+			let duration = sp_io::offchain::timestamp().diff(&started).millis();
+			update_stats(OCW_STATS_PREFIX, |stats| {
+				stats.runs += 1;
+				stats.last_duration_ms = duration;
+			});
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
 		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// 创建kitty
-		#[pallet::weight(10_000)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
 		#[frame_support::transactional]
 		pub fn create(origin: OriginFor<T>) -> DispatchResult {
 			// 校验是否是一个签名的交易并获取sender
@@ -208,12 +284,14 @@ pub mod pallet {
 			})?;
 			Self::save_kitty_to_indexing(kitty_id);
 			// 发送一个成功的事件
-			Self::deposit_event(Event::KittyCreated(sender, kitty_id, kitty));
+			let topics = [T::topic(&kitty_id), T::topic(&sender)];
+			let event = Event::KittyCreated(sender, kitty_id, kitty);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 			Ok({})
 		}
 
 		/// 孵化kitty
-		#[pallet::weight(10_000)]
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
 		#[frame_support::transactional]
 		pub fn breed(origin: OriginFor<T>, kitty_id_one: T::KittyIndex, kitty_id_two: T::KittyIndex) -> DispatchResult {
 			// 校验是否是一个签名的交易并获取sender
@@ -257,7 +335,14 @@ pub mod pallet {
 			})?;
 			Self::save_kitty_to_indexing(kitty_id);
 			// 发送一个成功的事件
-			Self::deposit_event(Event::KittyBreed(sender, kitty_id, kitty));
+			let topics = [
+				T::topic(&kitty_id),
+				T::topic(&sender),
+				T::topic(&kitty_id_one),
+				T::topic(&kitty_id_two),
+			];
+			let event = Event::KittyBreed(sender, kitty_id, kitty);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 			Ok({})
 		}
 
@@ -267,6 +352,35 @@ pub mod pallet {
 		pub fn transfer(origin: OriginFor<T>, kitty_id: T::KittyIndex, new_owner: T::AccountId) -> DispatchResult {
 			// 校验是否是一个签名的交易并获取sender
 			let sender = ensure_signed(origin)?;
+			Self::do_transfer(sender, kitty_id, new_owner)
+		}
+
+		#[pallet::weight(0)]
+		pub fn update_kitty(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			algebra: u32,
+		) -> DispatchResultWithPostInfo {
+			let _who = ensure_signed(origin)?;
+
+			let kitty = Self::get_kitty(kitty_id).map_err(|_| Error::<T>::InvalidKittyId)?;
+
+			let new_kitty = Kitty { dna: kitty.dna, algebra };
+
+			Kitties::<T>::insert(kitty_id, &new_kitty);
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 把 `sender` 的 kitty 转给 `new_owner`，合约的 chain extension 用合约账户作为 `sender` 调用
+		#[frame_support::transactional]
+		pub fn do_transfer(
+			sender: T::AccountId,
+			kitty_id: T::KittyIndex,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
 			let kitty_price = T::KittyPrice::get();
 			ensure!(T::Currency::can_reserve(&new_owner, kitty_price), Error::<T>::NotEnoughBalance);
 
@@ -277,35 +391,85 @@ pub mod pallet {
 			T::Currency::reserve(&new_owner, kitty_price)?;
 			KittyOwner::<T>::insert(kitty_id, &new_owner);
 
+			OwnerKitties::<T>::mutate(&sender, |kitties| kitties.retain(|id| *id != kitty_id));
 			OwnerKitties::<T>::try_mutate(&new_owner, |ref mut kitties| {
 				kitties.try_push(kitty_id).map_err(|_| Error::<T>::OwnTooManyKitties)?;
 				Ok::<(), DispatchError>(())
 			})?;
 
 			// 发送一个成功的事件
-			Self::deposit_event(Event::KittyTransfer(sender, kitty_id, new_owner));
+			let topics = [T::topic(&kitty_id), T::topic(&sender), T::topic(&new_owner)];
+			let event = Event::KittyTransfer(sender, kitty_id, new_owner);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 			Ok({})
 		}
 
-		#[pallet::weight(0)]
-		pub fn update_kitty(
-			origin: OriginFor<T>,
-			kitty_id: T::KittyIndex,
-			algebra: u32,
-		) -> DispatchResultWithPostInfo {
-			let _who = ensure_signed(origin)?;
+		/// 账户在当前滑动窗口内的创建记录，窗口以外的已经去掉
+		fn recent_creations(
+			who: &T::AccountId,
+		) -> BoundedVec<T::BlockNumber, T::MaxCreationsPerWindow> {
+			let mut recent = RecentCreations::<T>::get(who);
+			Self::retain_recent(&mut recent);
+			recent
+		}
 
-			let kitty = Self::get_kitty(kitty_id).map_err(|_| Error::<T>::InvalidKittyId)?;
+		/// 去掉滑出当前窗口的创建记录
+		fn retain_recent(recent: &mut BoundedVec<T::BlockNumber, T::MaxCreationsPerWindow>) {
+			let now = frame_system::Pallet::<T>::block_number();
+			recent.retain(|n| n.saturating_add(T::CreationWindow::get()) > now);
+		}
 
-			let new_kitty = Kitty { dna: kitty.dna, algebra };
+		/// 清理 `RecentCreations` 中滑出窗口的记录，全部过期的账户删除整个条目。
+		/// 每次最多处理 `MAX_CREATIONS_PRUNED_PER_BLOCK` 个账户，从上次停下的位置继续，返回消耗的权重
+		fn prune_recent_creations(remaining_weight: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let per_account = db_weight.reads_writes(1, 1);
+			let mut used = db_weight.reads_writes(1, 1);
+			if remaining_weight < used.saturating_add(per_account) {
+				return 0
+			}
 
-			Kitties::<T>::insert(kitty_id, &new_kitty);
+			let limit = (remaining_weight - used)
+				.checked_div(per_account)
+				.unwrap_or(u64::MAX)
+				.min(MAX_CREATIONS_PRUNED_PER_BLOCK.into()) as usize;
+			let mut iter = match RecentCreationsCursor::<T>::get() {
+				Some(cursor) => RecentCreations::<T>::iter_from(cursor),
+				None => RecentCreations::<T>::iter(),
+			};
+			let accounts = iter.by_ref().take(limit).collect::<Vec<_>>();
+			if accounts.len() < limit {
+				RecentCreationsCursor::<T>::kill();
+			} else {
+				RecentCreationsCursor::<T>::put(iter.last_raw_key().to_vec());
+			}
 
-			Ok(().into())
+			for (who, mut recent) in accounts {
+				let len = recent.len();
+				Self::retain_recent(&mut recent);
+				if recent.is_empty() {
+					RecentCreations::<T>::remove(&who);
+				} else if recent.len() != len {
+					RecentCreations::<T>::insert(&who, recent);
+				}
+				used = used.saturating_add(per_account);
+			}
+			used
+		}
+
+		/// 账户在当前滑动窗口内是否还能创建 kitty
+		pub fn can_create(who: &T::AccountId) -> bool {
+			(Self::recent_creations(who).len() as u32) < T::MaxCreationsPerWindow::get()
+		}
+
+		/// 记录账户在当前块的一次创建，滑动窗口内的次数已满时返回 `Err`
+		pub fn note_creation(who: &T::AccountId) -> Result<(), ()> {
+			let mut recent = Self::recent_creations(who);
+			recent.try_push(frame_system::Pallet::<T>::block_number()).map_err(|_| ())?;
+			RecentCreations::<T>::insert(who, recent);
+			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
 		/// get a random 256
 		fn random_value(sender: &T::AccountId) -> [u8; 16] {
 			let payload = (
@@ -334,15 +498,18 @@ pub mod pallet {
 		}
 
 		fn derived_key(block_number: T::BlockNumber) -> Vec<u8> {
-			block_number.using_encoded(|encoded_bn| {
-				UNCHAIN_TX_KEY
-					.clone()
-					.into_iter()
-					.chain(b"/".into_iter())
-					.chain(encoded_bn)
-					.copied()
-					.collect::<Vec<u8>>()
-			})
+			block_key(INDEXING_KEY_PREFIX, block_number)
+		}
+
+		/// 清理早于 `OffchainKeyRetention` 个块的链下索引。
+		/// 从上次清理到的块继续，每次最多清理 `MAX_PRUNE_PER_RUN` 个块
+		fn prune_indexing_keys(block_number: T::BlockNumber) {
+			let retention = T::OffchainKeyRetention::get();
+			if let Some((from, to)) =
+				prune_block_keys(PRUNE_CURSOR_KEY, INDEXING_KEY_PREFIX, block_number, retention)
+			{
+				log::debug!("Pruned kitty indexing keys of blocks {:?}..{:?}", from, to);
+			}
 		}
 
 		fn save_kitty_to_indexing(kitty_id: T::KittyIndex) {
@@ -368,7 +535,10 @@ pub mod pallet {
 
 			for (acc, res) in &results {
 				match res {
-					Ok(()) => log::info!("[{:?}] Submitted data:{:?}", acc.id, (kitty_id, payload)),
+					Ok(()) => {
+						log::info!("[{:?}] Submitted data:{:?}", acc.id, (kitty_id, payload));
+						update_stats(OCW_STATS_PREFIX, |stats| stats.submitted += 1);
+					},
 					Err(e) => log::error!("[{:?}] Failed to submit transaction: {:?}", acc.id, e),
 				}
 			}
@@ -376,6 +546,47 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// 存储的不变量：每个 kitty 都有所有者，id 小于 `NextKittyId`，
+		/// 并且只出现在所有者一个人的列表中，且只出现一次；
+		/// 所有者锁定的余额不少于 `KittyPrice` 乘以拥有的数量。
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			use frame_support::sp_std::collections::btree_map::BTreeMap;
+
+			let mut listed = BTreeMap::<T::KittyIndex, T::AccountId>::new();
+			for (owner, kitties) in OwnerKitties::<T>::iter() {
+				for kitty_id in kitties {
+					ensure!(
+						listed.insert(kitty_id, owner.clone()).is_none(),
+						"Kitty listed more than once in OwnerKitties"
+					);
+				}
+			}
+
+			let next_kitty_id = Self::next_kitty_id();
+			let mut owned = BTreeMap::<T::AccountId, u32>::new();
+			for kitty_id in Kitties::<T>::iter_keys() {
+				ensure!(kitty_id < next_kitty_id, "Kitty id not less than NextKittyId");
+				let owner = Self::kitty_owner(kitty_id).ok_or("Kitty without owner")?;
+				ensure!(
+					listed.remove(&kitty_id).as_ref() == Some(&owner),
+					"Kitty not in the OwnerKitties of its owner"
+				);
+				*owned.entry(owner).or_default() += 1;
+			}
+			ensure!(listed.is_empty(), "OwnerKitties contains a kitty that not exist");
+			ensure!(
+				KittyOwner::<T>::iter_keys().all(|kitty_id| Kitties::<T>::contains_key(kitty_id)),
+				"Owner of a kitty that not exist"
+			);
+			for (owner, count) in owned {
+				let price = T::KittyPrice::get().saturating_mul(count.into());
+				ensure!(
+					T::Currency::reserved_balance(&owner) >= price,
+					"KittyPrice not reserved for owned kitties"
+				);
+			}
+			Ok(())
+		}
 	}
 }
-
@@ -5,8 +5,8 @@ use frame_support::{
 };
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -22,7 +22,7 @@ frame_support::construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -68,6 +68,44 @@ impl pallet_balances::Config for Test {
 
 impl pallet_randomness_collective_flip::Config for Test {}
 
+type Extrinsic = TestXt<Call, ()>;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: UintAuthorityId,
+		_account: u64,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+/// 测试用的签名类型，签名不做真实校验
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
 parameter_types! {
 	pub const KittyPrice: u64 = 10;
 }
@@ -78,6 +116,10 @@ impl pallet_kitties::Config for Test {
 	type KittyIndex = u32;
 	type MaxKittyIndex = ConstU32<3>;
 	type KittyPrice = KittyPrice;
+	type AuthorityId = TestAuthId;
+	type OffchainKeyRetention = ConstU64<4>;
+	type MaxCreationsPerWindow = ConstU32<2>;
+	type CreationWindow = ConstU64<10>;
 }
 
 // Build genesis storage according to the mock runtime.
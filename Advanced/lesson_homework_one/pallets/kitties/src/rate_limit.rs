@@ -0,0 +1,88 @@
+//! 限制账户创建 kitty 频率的 `SignedExtension`。
+//!
+//! `create` 和 `breed` 在交易池验证和打包时检查签名账户最近 `CreationWindow` 个块内发起的次数，
+//! 达到 `MaxCreationsPerWindow` 后交易无效，不会上链，也不扣交易费。
+//! 次数在打包时记录，调用失败也会计入。
+
+use crate::{Call, Config, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_std::{fmt, marker::PhantomData},
+	traits::IsSubType,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+
+/// 超过频率限制时 `InvalidTransaction::Custom` 的错误码
+pub const RATE_LIMITED: u8 = 1;
+
+/// 加到 runtime 的 `SignedExtra` 中，限制 `create` 和 `breed` 的频率
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckKittyRateLimit<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckKittyRateLimit<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+
+	fn is_creation(call: &<T as frame_system::Config>::Call) -> bool
+	where
+		<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+	{
+		matches!(call.is_sub_type(), Some(Call::create { .. }) | Some(Call::breed { .. }))
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckKittyRateLimit<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckKittyRateLimit")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckKittyRateLimit<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckKittyRateLimit";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if Self::is_creation(call) && !Pallet::<T>::can_create(who) {
+			return InvalidTransaction::Custom(RATE_LIMITED).into()
+		}
+		Ok(ValidTransaction::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		if Self::is_creation(call) {
+			Pallet::<T>::note_creation(who)
+				.map_err(|_| InvalidTransaction::Custom(RATE_LIMITED))?;
+		}
+		Ok(())
+	}
+}
@@ -1,8 +1,17 @@
 use std::ops::Add;
 
 use super::*;
-use frame_support::{assert_noop, assert_ok};
-use mock::{new_test_ext, KittiesModule, Origin, System, Test};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GenesisBuild, Hooks},
+};
+use sp_core::offchain::{testing, OffchainDbExt, OffchainWorkerExt, StorageKind};
+use mock::{new_test_ext, Balances, Event as TestEvent, KittiesModule, Origin, System, Test};
+use pallet_support::{
+	events::IndexedEvents,
+	stats::{offchain_worker_stats, OffchainWorkerStats},
+};
 
 #[test]
 fn it_works_for_creating_kitty() {
@@ -180,6 +189,8 @@ fn it_works_for_transferring_kitty() {
 		assert_ok!(KittiesModule::transfer(Origin::signed(account_id_1), kitty_id, account_id_2));
 
 		assert_eq!(KittyOwner::<Test>::get(kitty_id), Some(account_id_2));
+		assert_eq!(OwnerKitties::<Test>::get(account_id_1).into_inner(), vec![]);
+		assert_eq!(OwnerKitties::<Test>::get(account_id_2).into_inner(), vec![kitty_id]);
 		assert_ne!(Kitties::<Test>::get(kitty_id), None);
 		assert_eq!(NextKittyId::<Test>::get(), kitty_id.add(&1));
 		assert_eq!(<Test as Config>::Currency::reserved_balance(&account_id_1), 0);
@@ -246,4 +257,210 @@ fn transfer_kitty_fails_for_too_many_kitties() {
 			Error::<Test>::OwnTooManyKitties
 		);
 	});
-}
\ No newline at end of file
+}
+
+/// 当前块中 kitties 模块事件的序号
+fn kitty_event_indexes() -> Vec<(u64, u32)> {
+	System::events()
+		.iter()
+		.enumerate()
+		.filter(|(_, record)| matches!(record.event, TestEvent::KittiesModule(_)))
+		.map(|(index, _)| (System::block_number(), index as u32))
+		.collect()
+}
+
+#[test]
+fn kitty_events_are_indexed_by_kitty_and_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let account_id_1: u64 = 0;
+		let account_id_2: u64 = 1;
+		let kitty_id: u32 = NextKittyId::<Test>::get();
+		assert_ok!(KittiesModule::create(Origin::signed(account_id_1)));
+		assert_ok!(KittiesModule::transfer(Origin::signed(account_id_1), kitty_id, account_id_2));
+
+		let indexes = kitty_event_indexes();
+		assert_eq!(indexes.len(), 2);
+		assert_eq!(System::event_topics(&Test::topic(&kitty_id)), indexes);
+		assert_eq!(System::event_topics(&Test::topic(&account_id_1)), indexes);
+		assert_eq!(System::event_topics(&Test::topic(&account_id_2)), vec![indexes[1]]);
+	});
+}
+
+#[test]
+fn breed_event_is_indexed_by_parents() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let account_id: u64 = 0;
+		let kitty_id_1: u32 = NextKittyId::<Test>::get();
+		assert_ok!(KittiesModule::create(Origin::signed(account_id)));
+		let kitty_id_2: u32 = NextKittyId::<Test>::get();
+		assert_ok!(KittiesModule::create(Origin::signed(account_id)));
+		System::reset_events();
+
+		let child_id: u32 = NextKittyId::<Test>::get();
+		assert_ok!(KittiesModule::breed(Origin::signed(account_id), kitty_id_1, kitty_id_2));
+
+		let indexes = kitty_event_indexes();
+		assert_eq!(System::event_topics(&Test::topic(&child_id)), indexes);
+		assert_eq!(System::event_topics(&Test::topic(&kitty_id_1)), indexes);
+		assert_eq!(System::event_topics(&Test::topic(&kitty_id_2)), indexes);
+	});
+}
+
+#[test]
+fn offchain_worker_prunes_old_indexing_keys() {
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+
+	let key = |n: u64| [INDEXING_KEY_PREFIX, &n.encode()].concat();
+	let exists = |n: u64| {
+		sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key(n)).is_some()
+	};
+
+	t.execute_with(|| {
+		for n in 0..100 {
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &key(n), &0u32.encode());
+		}
+
+		// 保留最近 4 个块，每次最多清理 64 个块
+		KittiesModule::offchain_worker(101);
+		assert!(!exists(63));
+		assert!(exists(64));
+
+		KittiesModule::offchain_worker(103);
+		assert!(!exists(98));
+		assert!(exists(99));
+
+		// 没有需要处理的 kitty，不提交交易
+		assert_eq!(
+			offchain_worker_stats(crate::OCW_STATS_PREFIX),
+			OffchainWorkerStats { runs: 2, last_duration_ms: 0, http_failures: 0, submitted: 0 }
+		);
+	});
+}
+
+#[test]
+fn genesis_kitties_are_created_and_reserved() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(0, 100), (1, 25)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	GenesisConfig::<Test> { kitties: vec![(0, [1; 16]), (1, [2; 16]), (0, [3; 16])] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_eq!(NextKittyId::<Test>::get(), 3);
+		assert_eq!(Kitties::<Test>::get(1), Some(Kitty { dna: [2; 16], algebra: 1 }));
+		assert_eq!(KittyOwner::<Test>::get(2), Some(0));
+		assert_eq!(OwnerKitties::<Test>::get(0).to_vec(), vec![0, 2]);
+		assert_eq!(Balances::reserved_balance(0), 20);
+		assert_eq!(Balances::reserved_balance(1), 10);
+
+		// 创世 kitty 和普通 kitty 一样可以转移
+		assert_ok!(KittiesModule::transfer(Origin::signed(1), 1, 0));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn try_state_checks_kitty_invariants() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(KittiesModule::create(Origin::signed(0)));
+		assert_ok!(KittiesModule::create(Origin::signed(0)));
+		assert_ok!(KittiesModule::breed(Origin::signed(0), 0, 1));
+		assert_ok!(KittiesModule::transfer(Origin::signed(0), 2, 1));
+		assert_ok!(KittiesModule::do_try_state());
+
+		// kitty 同时出现在原所有者的列表中
+		assert_ok!(OwnerKitties::<Test>::try_mutate(0, |kitties| kitties.try_push(2)));
+		assert_eq!(
+			KittiesModule::do_try_state(),
+			Err("Kitty listed more than once in OwnerKitties")
+		);
+		OwnerKitties::<Test>::mutate(0, |kitties| kitties.retain(|id| *id != 2));
+
+		// kitty 只在别人的列表中
+		OwnerKitties::<Test>::mutate(1, |kitties| kitties.retain(|id| *id != 2));
+		assert_ok!(OwnerKitties::<Test>::try_mutate(0, |kitties| kitties.try_push(2)));
+		assert_eq!(
+			KittiesModule::do_try_state(),
+			Err("Kitty not in the OwnerKitties of its owner")
+		);
+		OwnerKitties::<Test>::mutate(0, |kitties| kitties.retain(|id| *id != 2));
+		assert_ok!(OwnerKitties::<Test>::try_mutate(1, |kitties| kitties.try_push(2)));
+		assert_ok!(KittiesModule::do_try_state());
+
+		// 锁定的余额不够
+		<Test as Config>::Currency::unreserve(&1, <Test as Config>::KittyPrice::get());
+		assert_eq!(KittiesModule::do_try_state(), Err("KittyPrice not reserved for owned kitties"));
+
+		// kitty 没有所有者
+		KittyOwner::<Test>::remove(2);
+		assert_eq!(KittiesModule::do_try_state(), Err("Kitty without owner"));
+	});
+}
+
+#[test]
+fn rate_limit_rejects_creations_in_window() {
+	use frame_support::weights::DispatchInfo;
+	use sp_runtime::{
+		traits::SignedExtension,
+		transaction_validity::{InvalidTransaction, TransactionValidityError},
+	};
+
+	new_test_ext().execute_with(|| {
+		let check = CheckKittyRateLimit::<Test>::new();
+		let info = DispatchInfo::default();
+		let create: mock::Call = Call::<Test>::create {}.into();
+		let breed: mock::Call = Call::<Test>::breed { kitty_id_one: 0, kitty_id_two: 1 }.into();
+		let limited = TransactionValidityError::from(InvalidTransaction::Custom(RATE_LIMITED));
+
+		// 每 10 个块最多 2 次
+		System::set_block_number(1);
+		assert_ok!(check.clone().pre_dispatch(&0, &create, &info, 0));
+		System::set_block_number(5);
+		assert_ok!(check.clone().pre_dispatch(&0, &breed, &info, 0));
+		assert_eq!(check.validate(&0, &create, &info, 0), Err(limited));
+		assert_eq!(check.clone().pre_dispatch(&0, &breed, &info, 0), Err(limited));
+
+		// 其他账户和其他交易不受影响
+		assert_ok!(check.validate(&1, &create, &info, 0));
+		let transfer: mock::Call = Call::<Test>::transfer { kitty_id: 0, new_owner: 1 }.into();
+		assert_ok!(check.validate(&0, &transfer, &info, 0));
+
+		// 第 1 块的记录滑出窗口后可以再创建一次
+		System::set_block_number(11);
+		assert_ok!(check.clone().pre_dispatch(&0, &create, &info, 0));
+		assert_eq!(RecentCreations::<Test>::get(0).to_vec(), vec![5, 11]);
+		assert_eq!(check.validate(&0, &create, &info, 0), Err(limited));
+	});
+}
+
+#[test]
+fn on_idle_removes_expired_creations() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(KittiesModule::note_creation(&0));
+		System::set_block_number(5);
+		assert_ok!(KittiesModule::note_creation(&0));
+		assert_ok!(KittiesModule::note_creation(&1));
+
+		// 账户 0 第 1 块的记录滑出窗口，账户 1 的记录还在窗口内
+		System::set_block_number(11);
+		KittiesModule::on_idle(11, u64::MAX);
+		assert_eq!(RecentCreations::<Test>::get(0).to_vec(), vec![5]);
+		assert_eq!(RecentCreations::<Test>::get(1).to_vec(), vec![5]);
+
+		// 全部过期后删除整个条目
+		System::set_block_number(15);
+		KittiesModule::on_idle(15, u64::MAX);
+		assert!(!RecentCreations::<Test>::contains_key(0));
+		assert!(!RecentCreations::<Test>::contains_key(1));
+		assert_eq!(RecentCreationsCursor::<Test>::get(), None);
+	});
+}
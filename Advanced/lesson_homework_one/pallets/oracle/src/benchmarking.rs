@@ -0,0 +1,30 @@
+//! Benchmarking setup for pallet-oracle
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Oracle;
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::Get;
+use frame_system::RawOrigin;
+
+benchmarks! {
+	add_reporter {
+		let who: T::AccountId = account("reporter", 0, 0);
+	}: _(RawOrigin::Root, who.clone())
+	verify {
+		assert!(Reporters::<T>::get().contains(&who));
+	}
+
+	register_feed {
+		let u in 0 .. T::MaxFeedUrlLength::get();
+		let feed: T::FeedId = Default::default();
+		let url = vec![b'a'; u as usize];
+		let json_path = vec![b'a'; T::MaxJsonPathLength::get() as usize];
+	}: _(RawOrigin::Root, feed, url.clone(), json_path, 6, 10u32.into(), 1)
+	verify {
+		assert_eq!(Feeds::<T>::get(feed).map(|info| info.url.to_vec()), Some(url));
+	}
+
+	impl_benchmark_test_suite!(Oracle, crate::mock::new_test_ext(), crate::mock::Test);
+}
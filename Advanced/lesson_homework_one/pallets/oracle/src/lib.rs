@@ -0,0 +1,642 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! 预言机模块：多个授权的报告人按轮次提交链下数据，
+//! 一轮的报告数达到法定人数后取中位数作为数据源的最新值，偏离中位数太多的报告人会被记录。
+//!
+//! 其他模块通过 [`DataProvider::latest`] 读取最新值。
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+use codec::{Decode, Encode};
+use frame_support::RuntimeDebug;
+use frame_system::offchain::{SignedPayload, SigningTypes};
+use pallet_support::{json::json_value_at, stats::update_stats};
+use serde_json::Value;
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::traits::AtLeast32BitUnsigned;
+use sp_std::vec::Vec;
+
+/// 报告人签名用的 key 类型，通过 `author_insertKey` 插入
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"orcl");
+
+pub mod crypto {
+	pallet_support::offchain_crypto!(super::KEY_TYPE, OracleAuthId);
+}
+
+/// 轮次编号
+pub type RoundId = u32;
+
+/// 向其他模块提供预言机数据
+pub trait DataProvider<FeedId, Value> {
+	/// 数据源最近一次聚合出的值
+	fn latest(feed: &FeedId) -> Option<Value>;
+}
+
+impl<FeedId, Value> DataProvider<FeedId, Value> for () {
+	fn latest(_feed: &FeedId) -> Option<Value> {
+		None
+	}
+}
+
+/// 报告人对某个数据源某一轮的报告，由报告人的链下 key 签名
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct ReportPayload<Public, BlockNumber, FeedId, Value> {
+	pub feed: FeedId,
+	pub round: RoundId,
+	pub value: Value,
+	/// 链下工作机运行时的块
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: SigningTypes, FeedId: Encode, Value: Encode> SignedPayload<T>
+	for ReportPayload<T::Public, T::BlockNumber, FeedId, Value>
+{
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::{
+		offchain::{AppCrypto, ForAny, SendUnsignedTransaction, Signer},
+		pallet_prelude::*,
+	};
+	use sp_runtime::{
+		offchain::{
+			http,
+			storage::{StorageRetrievalError, StorageValueRef},
+			Duration,
+		},
+		traits::{IdentifyAccount, SaturatedConversion, Saturating, Zero},
+		transaction_validity::TransactionValidityError,
+		Perbill,
+	};
+
+	/// 数据源配置
+	#[derive(
+		Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct FeedInfo<T: Config> {
+		/// HTTP GET 的地址
+		pub url: BoundedVec<u8, T::MaxFeedUrlLength>,
+		/// 响应 JSON 中取值的路径，用 `.` 分隔，数组用下标，比如 `data.prices.0.usd`
+		pub json_path: BoundedVec<u8, T::MaxJsonPathLength>,
+		/// 上链的整数为 JSON 中的数乘以 `10^decimals`
+		pub decimals: u8,
+		/// 一轮聚合后隔多少个块开始报告下一轮
+		pub interval: T::BlockNumber,
+		/// 每轮至少多少个报告才能聚合
+		pub quorum: u32,
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + frame_system::offchain::SendTransactionTypes<Call<Self>> + SigningTypes
+	{
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// 报告人签名用的 key
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// 数据源的标识
+		type FeedId: Parameter + Member + MaxEncodedLen + Copy + Ord + Default;
+
+		/// 上链的数值，小数按数据源的 `decimals` 放大成整数
+		type Value: Parameter + Member + MaxEncodedLen + Copy + AtLeast32BitUnsigned;
+
+		/// 报告人的最大数量
+		#[pallet::constant]
+		type MaxReporters: Get<u32>;
+
+		/// 报告偏离中位数超过中位数的这个比例时记为异常
+		#[pallet::constant]
+		type OutlierThreshold: Get<Perbill>;
+
+		/// 签名报告在多少个块之后过期，过期后链下工作机会重新报告
+		#[pallet::constant]
+		type UnsignedLongevity: Get<Self::BlockNumber>;
+
+		/// 一轮从第一个报告开始，过了这么多块还没有达到法定人数时，已有的报告作废，重新收集
+		#[pallet::constant]
+		type RoundTimeout: Get<Self::BlockNumber>;
+
+		/// 数据源 URL 的最大长度
+		#[pallet::constant]
+		type MaxFeedUrlLength: Get<u32>;
+
+		/// JSON 路径的最大长度
+		#[pallet::constant]
+		type MaxJsonPathLength: Get<u32>;
+	}
+
+	/// 授权的报告人
+	#[pallet::storage]
+	#[pallet::getter(fn reporters)]
+	pub type Reporters<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxReporters>, ValueQuery>;
+
+	/// 数据源配置，只能由 root 设置
+	#[pallet::storage]
+	#[pallet::getter(fn feeds)]
+	pub type Feeds<T: Config> = StorageMap<_, Twox64Concat, T::FeedId, FeedInfo<T>>;
+
+	/// 数据源当前收集报告的轮次
+	#[pallet::storage]
+	#[pallet::getter(fn current_round)]
+	pub type CurrentRound<T: Config> = StorageMap<_, Twox64Concat, T::FeedId, RoundId, ValueQuery>;
+
+	/// 当前轮次已经收到的报告
+	#[pallet::storage]
+	#[pallet::getter(fn reports)]
+	pub type Reports<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::FeedId,
+		BoundedVec<(T::AccountId, T::Value), T::MaxReporters>,
+		ValueQuery,
+	>;
+
+	/// 当前轮次收到第一个报告的块
+	#[pallet::storage]
+	#[pallet::getter(fn round_started_at)]
+	pub type RoundStartedAt<T: Config> = StorageMap<_, Twox64Concat, T::FeedId, T::BlockNumber>;
+
+	/// 数据源最近一次聚合的结果：中位数，轮次以及所在的块
+	#[pallet::storage]
+	#[pallet::getter(fn latest_value)]
+	pub type Latest<T: Config> =
+		StorageMap<_, Twox64Concat, T::FeedId, (T::Value, RoundId, T::BlockNumber)>;
+
+	/// 报告人的报告偏离中位数的次数
+	#[pallet::storage]
+	#[pallet::getter(fn outlier_count)]
+	pub type OutlierCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub reporters: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { reporters: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let reporters: BoundedVec<_, T::MaxReporters> =
+				self.reporters.clone().try_into().expect("too many reporters in genesis");
+			<Reporters<T>>::put(reporters);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// 新增报告人 [who]
+		ReporterAdded(T::AccountId),
+		/// 移除报告人 [who]
+		ReporterRemoved(T::AccountId),
+		/// 数据源配置已更新 [feed]
+		FeedRegistered(T::FeedId),
+		/// 数据源已删除 [feed]
+		FeedRemoved(T::FeedId),
+		/// 收到报告 [feed, round, who, value]
+		Reported(T::FeedId, RoundId, T::AccountId, T::Value),
+		/// 一轮报告已聚合 [feed, round, median]
+		RoundFinalized(T::FeedId, RoundId, T::Value),
+		/// 报告偏离中位数太多 [feed, round, who, value, median]
+		OutlierReported(T::FeedId, RoundId, T::AccountId, T::Value, T::Value),
+		/// 一轮超时没有达到法定人数，已有的报告作废 [feed, round]
+		RoundReset(T::FeedId, RoundId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// 已经是报告人
+		AlreadyReporter,
+		/// 不是报告人
+		NotReporter,
+		/// 报告人太多
+		TooManyReporters,
+		/// 数据源 URL 太长
+		FeedUrlTooLong,
+		/// JSON 路径太长
+		JsonPathTooLong,
+		/// 间隔不能为 0
+		ZeroFeedInterval,
+		/// 法定人数必须在 1 到报告人最大数量之间
+		InvalidQuorum,
+		/// 数据源不存在
+		FeedNotExist,
+		/// 不是当前轮次的报告
+		WrongRound,
+		/// 这一轮已经报告过
+		AlreadyReported,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// 新增报告人
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			<Reporters<T>>::try_mutate(|reporters| {
+				ensure!(!reporters.contains(&who), Error::<T>::AlreadyReporter);
+				reporters.try_push(who.clone()).map_err(|_| Error::<T>::TooManyReporters)
+			})?;
+
+			Self::deposit_event(Event::ReporterAdded(who));
+			Ok(())
+		}
+
+		/// 移除报告人，已经提交的报告仍然参与本轮聚合
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			<Reporters<T>>::try_mutate(|reporters| {
+				let index =
+					reporters.iter().position(|r| r == &who).ok_or(Error::<T>::NotReporter)?;
+				reporters.remove(index);
+				Ok::<(), Error<T>>(())
+			})?;
+
+			Self::deposit_event(Event::ReporterRemoved(who));
+			Ok(())
+		}
+
+		/// 新增或修改数据源
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn register_feed(
+			origin: OriginFor<T>,
+			feed: T::FeedId,
+			url: Vec<u8>,
+			json_path: Vec<u8>,
+			decimals: u8,
+			interval: T::BlockNumber,
+			quorum: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!interval.is_zero(), Error::<T>::ZeroFeedInterval);
+			ensure!(quorum > 0 && quorum <= T::MaxReporters::get(), Error::<T>::InvalidQuorum);
+			let url = url.try_into().map_err(|_| Error::<T>::FeedUrlTooLong)?;
+			let json_path = json_path.try_into().map_err(|_| Error::<T>::JsonPathTooLong)?;
+
+			<Feeds<T>>::insert(feed, FeedInfo { url, json_path, decimals, interval, quorum });
+			Self::deposit_event(Event::FeedRegistered(feed));
+			Ok(())
+		}
+
+		/// 删除数据源以及当前轮次的报告，最近一次聚合的结果保留
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3))]
+		pub fn remove_feed(origin: OriginFor<T>, feed: T::FeedId) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(<Feeds<T>>::contains_key(feed), Error::<T>::FeedNotExist);
+
+			<Feeds<T>>::remove(feed);
+			<Reports<T>>::remove(feed);
+			<RoundStartedAt<T>>::remove(feed);
+			Self::deposit_event(Event::FeedRemoved(feed));
+			Ok(())
+		}
+
+		/// 链下工作机提交报告，`payload` 由报告人签名。
+		/// 按最多 `MaxReporters` 个报告聚合预留权重，返回实际聚合的报告数对应的权重
+		#[pallet::weight(Pallet::<T>::report_weight(T::MaxReporters::get()))]
+		pub fn submit_report_unsigned(
+			origin: OriginFor<T>,
+			payload: ReportPayload<T::Public, T::BlockNumber, T::FeedId, T::Value>,
+			_signature: T::Signature,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			let who = payload.public.into_account();
+			let aggregated = Self::do_report(who, payload.feed, payload.round, payload.value)?;
+			Ok(Some(Self::report_weight(aggregated)).into())
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(block_number: T::BlockNumber) {
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			if !signer.can_sign() {
+				log::debug!("No local key for {:?}, skip oracle reports", KEY_TYPE);
+				return
+			}
+
+			let started = sp_io::offchain::timestamp();
+			for (feed, info) in <Feeds<T>>::iter() {
+				if let Err(e) = Self::report_feed(&signer, feed, &info, block_number) {
+					log::warn!("Oracle report for feed {:?} failed: {}", feed, e);
+				}
+			}
+
+			let duration = sp_io::offchain::timestamp().diff(&started).millis();
+			update_stats(OCW_STATS_PREFIX, |stats| {
+				stats.runs += 1;
+				stats.last_duration_ms = duration;
+			});
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			match call {
+				Call::submit_report_unsigned { payload, signature } => {
+					let (who, longevity) = Self::validate_report(payload, signature)?;
+					ValidTransaction::with_tag_prefix("OracleReport")
+						.priority(10000)
+						.and_provides((payload.feed, payload.round, who))
+						.longevity(longevity)
+						.propagate(true)
+						.build()
+				},
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
+	}
+
+	impl<T: Config> DataProvider<T::FeedId, T::Value> for Pallet<T> {
+		fn latest(feed: &T::FeedId) -> Option<T::Value> {
+			<Latest<T>>::get(feed).map(|(value, _, _)| value)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// `submit_report_unsigned` 的权重，`aggregated` 是这次聚合的报告数，
+		/// 聚合时每个报告都要和中位数比较，偏离太多时更新 `OutlierCount`
+		pub fn report_weight(aggregated: u32) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let per_report = db_weight.reads_writes(1, 1).saturating_add(1_000);
+			db_weight
+				.reads_writes(5, 5)
+				.saturating_add(10_000)
+				.saturating_add(per_report.saturating_mul(aggregated.into()))
+		}
+
+		/// 当前轮次是否已经超时：收到第一个报告后过了 `RoundTimeout` 个块
+		fn round_timed_out(feed: T::FeedId, now: T::BlockNumber) -> bool {
+			<RoundStartedAt<T>>::get(feed)
+				.map_or(false, |started| now >= started.saturating_add(T::RoundTimeout::get()))
+		}
+
+		/// 记录报告，达到法定人数时聚合这一轮，返回聚合的报告数，没有聚合时为 0。
+		/// 当前轮次超时的话先作废已有的报告
+		fn do_report(
+			who: T::AccountId,
+			feed: T::FeedId,
+			round: RoundId,
+			value: T::Value,
+		) -> Result<u32, DispatchError> {
+			ensure!(Self::reporters().contains(&who), Error::<T>::NotReporter);
+			let info = <Feeds<T>>::get(feed).ok_or(Error::<T>::FeedNotExist)?;
+			ensure!(round == <CurrentRound<T>>::get(feed), Error::<T>::WrongRound);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			if Self::round_timed_out(feed, now) {
+				<Reports<T>>::remove(feed);
+				<RoundStartedAt<T>>::remove(feed);
+				Self::deposit_event(Event::RoundReset(feed, round));
+			}
+
+			let count = <Reports<T>>::try_mutate(feed, |reports| {
+				ensure!(!reports.iter().any(|(r, _)| r == &who), Error::<T>::AlreadyReported);
+				reports.try_push((who.clone(), value)).map_err(|_| Error::<T>::TooManyReporters)?;
+				Ok::<_, Error<T>>(reports.len() as u32)
+			})?;
+			if count == 1 {
+				<RoundStartedAt<T>>::insert(feed, now);
+			}
+			Self::deposit_event(Event::Reported(feed, round, who, value));
+
+			if count < info.quorum {
+				return Ok(0)
+			}
+			Self::finalize_round(feed, round);
+			Ok(count)
+		}
+
+		/// 取中位数作为最新值，记录偏离中位数太多的报告人，进入下一轮
+		fn finalize_round(feed: T::FeedId, round: RoundId) {
+			let reports = <Reports<T>>::take(feed);
+			<RoundStartedAt<T>>::remove(feed);
+			let median = match median(reports.iter().map(|(_, value)| *value).collect()) {
+				Some(median) => median,
+				None => return,
+			};
+
+			<Latest<T>>::insert(feed, (median, round, <frame_system::Pallet<T>>::block_number()));
+			<CurrentRound<T>>::insert(feed, round.saturating_add(1));
+			Self::deposit_event(Event::RoundFinalized(feed, round, median));
+
+			let threshold = T::OutlierThreshold::get().mul_floor(median);
+			for (who, value) in reports {
+				let deviation = if value > median { value - median } else { median - value };
+				if deviation > threshold {
+					<OutlierCount<T>>::mutate(&who, |count| *count = count.saturating_add(1));
+					Self::deposit_event(Event::OutlierReported(feed, round, who, value, median));
+				}
+			}
+		}
+
+		/// 校验签名报告：签名正确，来自报告人，是当前轮次的第一次报告并且没有过期。
+		/// 返回报告人以及交易在交易池中还能存活的块数
+		fn validate_report(
+			payload: &ReportPayload<T::Public, T::BlockNumber, T::FeedId, T::Value>,
+			signature: &T::Signature,
+		) -> Result<(T::AccountId, u64), TransactionValidityError> {
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+				return Err(InvalidTransaction::BadProof.into())
+			}
+
+			let who = payload.public.clone().into_account();
+			if !Self::reporters().contains(&who) {
+				return Err(InvalidTransaction::BadSigner.into())
+			}
+			if !<Feeds<T>>::contains_key(payload.feed) {
+				return Err(InvalidTransaction::Call.into())
+			}
+
+			let round = <CurrentRound<T>>::get(payload.feed);
+			if payload.round < round {
+				return Err(InvalidTransaction::Stale.into())
+			}
+			if payload.round > round {
+				return Err(InvalidTransaction::Future.into())
+			}
+			// 超时的轮次会在报告上链时重新收集，已经报告过的报告人可以再报告
+			let now = <frame_system::Pallet<T>>::block_number();
+			if !Self::round_timed_out(payload.feed, now) &&
+				<Reports<T>>::get(payload.feed).iter().any(|(r, _)| r == &who)
+			{
+				return Err(InvalidTransaction::Stale.into())
+			}
+
+			if payload.block_number > now {
+				return Err(InvalidTransaction::Future.into())
+			}
+			let expires_at = payload.block_number.saturating_add(T::UnsignedLongevity::get());
+			if now > expires_at {
+				return Err(InvalidTransaction::Stale.into())
+			}
+
+			Ok((who, (expires_at - now).saturated_into::<u64>().max(1)))
+		}
+
+		/// 上一轮聚合后是否已经过了间隔
+		fn report_due(feed: T::FeedId, info: &FeedInfo<T>, now: T::BlockNumber) -> bool {
+			match <Latest<T>>::get(feed) {
+				Some((_, _, at)) => now >= at.saturating_add(info.interval),
+				None => true,
+			}
+		}
+
+		/// 拉取数据源并提交当前轮次的报告
+		fn report_feed(
+			signer: &Signer<T, T::AuthorityId, ForAny>,
+			feed: T::FeedId,
+			info: &FeedInfo<T>,
+			block_number: T::BlockNumber,
+		) -> Result<(), &'static str> {
+			if !Self::report_due(feed, info, block_number) {
+				return Ok(())
+			}
+
+			// 每轮只报告一次，交易上链前的几个块里不重复拉取。
+			// 报告过期以后还在同一轮的话说明交易没有上链或者这一轮被重置，重新报告
+			let round = <CurrentRound<T>>::get(feed);
+			let key = (REPORT_LOCK_PREFIX, feed).encode();
+			let mut lock = StorageValueRef::persistent(&key);
+			let res = lock.mutate(
+				|last: Result<Option<(RoundId, T::BlockNumber)>, StorageRetrievalError>| match last {
+					Ok(Some((last, at)))
+						if last >= round &&
+							block_number < at.saturating_add(T::UnsignedLongevity::get()) =>
+						Err(()),
+					_ => Ok((round, block_number)),
+				},
+			);
+			if res.is_err() {
+				return Ok(())
+			}
+
+			let value = Self::fetch_value(info);
+			if value.is_err() {
+				update_stats(OCW_STATS_PREFIX, |stats| stats.http_failures += 1);
+			}
+			let res = value.and_then(|value| {
+				log::info!("Oracle report for feed {:?} round {}: {:?}", feed, round, value);
+				match signer.send_unsigned_transaction(
+					|account| ReportPayload {
+						feed,
+						round,
+						value,
+						block_number,
+						public: account.public.clone(),
+					},
+					|payload, signature| Call::submit_report_unsigned { payload, signature },
+				) {
+					Some((_, Ok(()))) => {
+						update_stats(OCW_STATS_PREFIX, |stats| stats.submitted += 1);
+						Ok(())
+					},
+					_ => Err("Failed to submit report"),
+				}
+			});
+			if res.is_err() {
+				// 下一个块重试
+				lock.clear();
+			}
+			res
+		}
+
+		/// 请求数据源，按 JSON 路径取出数值并按 `decimals` 放大成整数
+		pub(crate) fn fetch_value(info: &FeedInfo<T>) -> Result<T::Value, &'static str> {
+			let url = sp_std::str::from_utf8(&info.url).map_err(|_| "Feed url is not UTF8")?;
+
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(8_000));
+			let pending = http::Request::get(url)
+				.add_header("User-Agent", "Substrate-Offchain-Worker")
+				.deadline(deadline)
+				.send()
+				.map_err(|_| "Failed to send request")?;
+			let response = pending
+				.try_wait(deadline)
+				.map_err(|_| "Request deadline reached")?
+				.map_err(|_| "Request failed")?;
+			if response.code != 200 {
+				log::warn!("Unexpected status code: {}", response.code);
+				return Err("Unexpected status code")
+			}
+			let body = response.body().collect::<Vec<u8>>();
+
+			let json: Value = serde_json::from_slice(&body).map_err(|_| "Response is not JSON")?;
+			let text = json_value_at(&json, &info.json_path).ok_or("No value at json path")?;
+			let value = parse_fixed(&text, info.decimals).ok_or("Value is not a decimal number")?;
+			T::Value::try_from(value).map_err(|_| "Value overflow")
+		}
+	}
+}
+
+/// 链下存储中记录每个数据源最近一次报告的轮次和块
+const REPORT_LOCK_PREFIX: &[u8] = b"oracle::report::last-round";
+
+/// 链下工作机运行统计的前缀，统计由 `pallet_support::stats` 保存在链下存储中
+pub const OCW_STATS_PREFIX: &[u8] = b"oracle";
+
+/// 中位数，偶数个时取中间两个的平均值（向下取整）
+pub fn median<V: AtLeast32BitUnsigned + Copy>(mut values: Vec<V>) -> Option<V> {
+	if values.is_empty() {
+		return None
+	}
+	values.sort();
+
+	let mid = values.len() / 2;
+	if values.len() % 2 == 1 {
+		return Some(values[mid])
+	}
+	// 分开除以 2，避免相加溢出
+	let (a, b, two) = (values[mid - 1], values[mid], V::from(2u8));
+	Some(a / two + b / two + (a % two + b % two) / two)
+}
+
+/// 把十进制小数文本放大 `10^decimals` 倍成整数，多余的小数位直接截断
+fn parse_fixed(text: &[u8], decimals: u8) -> Option<u128> {
+	let text = sp_std::str::from_utf8(text).ok()?;
+	let (int, frac) = text.split_once('.').unwrap_or((text, ""));
+	if int.is_empty() || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+		return None
+	}
+
+	let mut value: u128 = int.parse().ok()?;
+	for i in 0..decimals as usize {
+		let digit = frac.as_bytes().get(i).map_or(0, |b| (b - b'0') as u128);
+		value = value.checked_mul(10)?.checked_add(digit)?;
+	}
+	Some(value)
+}
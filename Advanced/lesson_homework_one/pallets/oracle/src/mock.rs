@@ -0,0 +1,110 @@
+use crate as pallet_oracle;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Oracle: pallet_oracle::{Pallet, Call, Storage, Config<T>, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+/// 测试用的签名类型，`UintAuthorityId(n)` 对应账户 `n`
+pub struct TestAuthId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+parameter_types! {
+	pub const OutlierThreshold: Perbill = Perbill::from_percent(10);
+}
+
+impl pallet_oracle::Config for Test {
+	type Event = Event;
+	type AuthorityId = TestAuthId;
+	type FeedId = u32;
+	type Value = u64;
+	type MaxReporters = ConstU32<4>;
+	type OutlierThreshold = OutlierThreshold;
+	type UnsignedLongevity = ConstU64<5>;
+	type RoundTimeout = ConstU64<20>;
+	type MaxFeedUrlLength = ConstU32<256>;
+	type MaxJsonPathLength = ConstU32<64>;
+}
+
+/// 创世时的报告人
+pub const REPORTERS: [u64; 3] = [1, 2, 3];
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	// 链下工作机本地只有报告人 1 的 key
+	UintAuthorityId::set_all_keys(vec![REPORTERS[0]]);
+
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_oracle::GenesisConfig::<Test> { reporters: REPORTERS.to_vec() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	t.into()
+}
@@ -0,0 +1,338 @@
+use crate::{mock::*, DataProvider, Error, ReportPayload};
+use codec::{Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok, dispatch::DispatchResultWithPostInfo, traits::Hooks,
+	unsigned::ValidateUnsigned,
+};
+use pallet_support::stats::{offchain_worker_stats, OffchainWorkerStats};
+use sp_core::offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt};
+use sp_runtime::{
+	testing::{TestSignature, UintAuthorityId},
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidityError},
+	DispatchError,
+};
+
+const FEED: u32 = 7;
+const FEED_URL: &str = "https://api.example.com/price";
+
+fn register_feed(quorum: u32) {
+	assert_ok!(Oracle::register_feed(
+		Origin::root(),
+		FEED,
+		FEED_URL.as_bytes().to_vec(),
+		b"data.usd".to_vec(),
+		2,
+		10,
+		quorum
+	));
+}
+
+/// `who` 签名的报告
+fn report_payload(
+	who: u64,
+	round: u32,
+	value: u64,
+) -> (ReportPayload<UintAuthorityId, u64, u32, u64>, TestSignature) {
+	let payload = ReportPayload {
+		feed: FEED,
+		round,
+		value,
+		block_number: System::block_number(),
+		public: UintAuthorityId(who),
+	};
+	let signature = TestSignature(who, payload.encode());
+	(payload, signature)
+}
+
+fn report(who: u64, round: u32, value: u64) -> DispatchResultWithPostInfo {
+	let (payload, signature) = report_payload(who, round, value);
+	Oracle::submit_report_unsigned(Origin::none(), payload, signature)
+}
+
+fn validate(who: u64, round: u32, value: u64) -> Result<(), InvalidTransaction> {
+	let (payload, signature) = report_payload(who, round, value);
+	let call = crate::Call::submit_report_unsigned { payload, signature };
+	match Oracle::validate_unsigned(TransactionSource::External, &call) {
+		Ok(_) => Ok(()),
+		Err(TransactionValidityError::Invalid(e)) => Err(e),
+		Err(e) => panic!("unexpected validity error: {:?}", e),
+	}
+}
+
+#[test]
+fn reporters_are_managed_by_root() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(Oracle::add_reporter(Origin::signed(1), 4), DispatchError::BadOrigin);
+		assert_noop!(Oracle::add_reporter(Origin::root(), 1), Error::<Test>::AlreadyReporter);
+		assert_ok!(Oracle::add_reporter(Origin::root(), 4));
+		System::assert_last_event(crate::Event::ReporterAdded(4).into());
+		assert_noop!(Oracle::add_reporter(Origin::root(), 5), Error::<Test>::TooManyReporters);
+
+		assert_ok!(Oracle::remove_reporter(Origin::root(), 2));
+		assert_eq!(Oracle::reporters().to_vec(), vec![1, 3, 4]);
+		assert_noop!(Oracle::remove_reporter(Origin::root(), 2), Error::<Test>::NotReporter);
+	});
+}
+
+#[test]
+fn register_feed_checks_input() {
+	new_test_ext().execute_with(|| {
+		let url = FEED_URL.as_bytes().to_vec();
+		assert_noop!(
+			Oracle::register_feed(Origin::root(), FEED, url.clone(), vec![], 2, 0, 1),
+			Error::<Test>::ZeroFeedInterval
+		);
+		assert_noop!(
+			Oracle::register_feed(Origin::root(), FEED, url.clone(), vec![], 2, 10, 0),
+			Error::<Test>::InvalidQuorum
+		);
+		assert_noop!(
+			Oracle::register_feed(Origin::root(), FEED, url, vec![], 2, 10, 5),
+			Error::<Test>::InvalidQuorum
+		);
+		assert_noop!(
+			Oracle::register_feed(Origin::root(), FEED, vec![b'a'; 257], vec![], 2, 10, 1),
+			Error::<Test>::FeedUrlTooLong
+		);
+
+		register_feed(3);
+		assert_eq!(Oracle::feeds(FEED).map(|info| info.quorum), Some(3));
+		assert_ok!(Oracle::remove_feed(Origin::root(), FEED));
+		assert_noop!(Oracle::remove_feed(Origin::root(), FEED), Error::<Test>::FeedNotExist);
+	});
+}
+
+#[test]
+fn median_is_stored_once_quorum_is_reached() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+
+		assert_ok!(report(1, 0, 100));
+		assert_ok!(report(2, 0, 110));
+		assert_eq!(Oracle::latest(&FEED), None);
+		assert_eq!(Oracle::reports(FEED).len(), 2);
+
+		assert_ok!(report(3, 0, 105));
+		System::assert_last_event(crate::Event::RoundFinalized(FEED, 0, 105).into());
+		assert_eq!(Oracle::latest(&FEED), Some(105));
+		assert_eq!(Oracle::latest_value(FEED), Some((105, 0, 1)));
+		assert_eq!(Oracle::current_round(FEED), 1);
+		assert!(Oracle::reports(FEED).is_empty());
+		assert_eq!(<() as DataProvider<u32, u64>>::latest(&FEED), None);
+	});
+}
+
+#[test]
+fn report_fails_for_invalid_reports() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(report(1, 0, 100), Error::<Test>::FeedNotExist);
+
+		register_feed(3);
+		assert_noop!(report(4, 0, 100), Error::<Test>::NotReporter);
+		assert_noop!(report(1, 1, 100), Error::<Test>::WrongRound);
+		assert_ok!(report(1, 0, 100));
+		assert_noop!(report(1, 0, 101), Error::<Test>::AlreadyReported);
+	});
+}
+
+#[test]
+fn outlier_reporters_are_tracked() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+
+		assert_ok!(report(1, 0, 100));
+		assert_ok!(report(2, 0, 150));
+		assert_ok!(report(3, 0, 108));
+
+		// 中位数 108，允许偏离 10 以内
+		assert_eq!(Oracle::latest(&FEED), Some(108));
+		assert_eq!(Oracle::outlier_count(1), 0);
+		assert_eq!(Oracle::outlier_count(2), 1);
+		assert_eq!(Oracle::outlier_count(3), 0);
+		System::assert_has_event(crate::Event::OutlierReported(FEED, 0, 2, 150, 108).into());
+	});
+}
+
+#[test]
+fn report_weight_depends_on_aggregated_reports() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+
+		let weight = |result: DispatchResultWithPostInfo| result.unwrap().actual_weight;
+		assert_eq!(weight(report(1, 0, 100)), Some(Oracle::report_weight(0)));
+		assert_eq!(weight(report(2, 0, 110)), Some(Oracle::report_weight(0)));
+		assert_eq!(weight(report(3, 0, 105)), Some(Oracle::report_weight(3)));
+		assert!(Oracle::report_weight(3) > Oracle::report_weight(0));
+	});
+}
+
+#[test]
+fn timed_out_round_is_reset() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+		assert_ok!(report(1, 0, 100));
+		assert_ok!(report(2, 0, 110));
+		assert_eq!(Oracle::round_started_at(FEED), Some(1));
+
+		// 超时前不能重复报告
+		System::set_block_number(20);
+		assert_eq!(validate(1, 0, 120), Err(InvalidTransaction::Stale));
+		assert_noop!(report(1, 0, 120), Error::<Test>::AlreadyReported);
+
+		// 超时后已有的报告作废，这一轮从新的报告重新开始
+		System::set_block_number(21);
+		assert_eq!(validate(1, 0, 120), Ok(()));
+		assert_ok!(report(1, 0, 120));
+		System::assert_has_event(crate::Event::RoundReset(FEED, 0).into());
+		assert_eq!(Oracle::reports(FEED).to_vec(), vec![(1, 120)]);
+		assert_eq!(Oracle::round_started_at(FEED), Some(21));
+		assert_eq!(Oracle::current_round(FEED), 0);
+
+		assert_ok!(report(2, 0, 121));
+		assert_ok!(report(3, 0, 122));
+		assert_eq!(Oracle::latest_value(FEED), Some((121, 0, 21)));
+		assert_eq!(Oracle::round_started_at(FEED), None);
+	});
+}
+
+#[test]
+fn median_works() {
+	assert_eq!(crate::median::<u64>(vec![]), None);
+	assert_eq!(crate::median(vec![5u64]), Some(5));
+	assert_eq!(crate::median(vec![9u64, 1, 5]), Some(5));
+	assert_eq!(crate::median(vec![4u64, 1, 8, 3]), Some(3));
+	assert_eq!(crate::median(vec![u64::MAX, u64::MAX]), Some(u64::MAX));
+}
+
+#[test]
+fn parse_fixed_works() {
+	assert_eq!(crate::parse_fixed(b"12", 2), Some(1200));
+	assert_eq!(crate::parse_fixed(b"1.5", 2), Some(150));
+	assert_eq!(crate::parse_fixed(b"1.239", 2), Some(123));
+	assert_eq!(crate::parse_fixed(b"0.07", 0), Some(0));
+	assert_eq!(crate::parse_fixed(b"-1", 2), None);
+	assert_eq!(crate::parse_fixed(b"1e3", 2), None);
+	assert_eq!(crate::parse_fixed(b".5", 2), None);
+}
+
+#[test]
+fn validate_unsigned_rejects_invalid_reports() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(5);
+		assert_eq!(validate(1, 0, 100), Err(InvalidTransaction::Call));
+
+		register_feed(3);
+		assert_eq!(validate(1, 0, 100), Ok(()));
+		assert_eq!(validate(4, 0, 100), Err(InvalidTransaction::BadSigner));
+		assert_eq!(validate(1, 1, 100), Err(InvalidTransaction::Future));
+
+		// 签名和报告不一致
+		let (mut payload, signature) = report_payload(1, 0, 100);
+		payload.value = 1_000;
+		let call = crate::Call::submit_report_unsigned { payload, signature };
+		assert_eq!(
+			Oracle::validate_unsigned(TransactionSource::External, &call),
+			Err(InvalidTransaction::BadProof.into())
+		);
+
+		assert_ok!(report(1, 0, 100));
+		assert_eq!(validate(1, 0, 100), Err(InvalidTransaction::Stale));
+		assert_ok!(report(2, 0, 100));
+		assert_ok!(report(3, 0, 100));
+		assert_eq!(validate(2, 0, 100), Err(InvalidTransaction::Stale));
+		assert_eq!(validate(2, 1, 100), Ok(()));
+	});
+}
+
+#[test]
+fn offchain_worker_reports_once_per_round() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: FEED_URL.into(),
+		headers: vec![("User-Agent".into(), "Substrate-Offchain-Worker".into())],
+		response: Some(br#"{"data":{"usd":"12.345"}}"#.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+
+	t.execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+		Oracle::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		match Extrinsic::decode(&mut &*tx).unwrap().call {
+			Call::Oracle(crate::Call::submit_report_unsigned { payload, signature }) => {
+				assert_eq!((payload.feed, payload.round, payload.value), (FEED, 0, 1234));
+				assert_eq!(payload.public, UintAuthorityId(1));
+				assert_eq!(signature, TestSignature(1, payload.encode()));
+			},
+			call => panic!("unexpected call: {:?}", call),
+		}
+
+		// 同一轮不再请求数据源
+		Oracle::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(
+			offchain_worker_stats(crate::OCW_STATS_PREFIX),
+			OffchainWorkerStats { runs: 2, last_duration_ms: 0, http_failures: 0, submitted: 1 }
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_reports_again_after_the_report_expires() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	for _ in 0..2 {
+		state.write().expect_request(testing::PendingRequest {
+			method: "GET".into(),
+			uri: FEED_URL.into(),
+			headers: vec![("User-Agent".into(), "Substrate-Offchain-Worker".into())],
+			response: Some(br#"{"data":{"usd":"12.345"}}"#.to_vec()),
+			sent: true,
+			..Default::default()
+		});
+	}
+
+	t.execute_with(|| {
+		System::set_block_number(1);
+		register_feed(3);
+		Oracle::offchain_worker(1);
+		assert_eq!(pool_state.read().transactions.len(), 1);
+
+		// 报告在 UnsignedLongevity 个块内有效，这期间不重复报告
+		Oracle::offchain_worker(5);
+		assert_eq!(pool_state.read().transactions.len(), 1);
+
+		// 报告过期了还没有上链，重新报告这一轮
+		System::set_block_number(6);
+		Oracle::offchain_worker(6);
+		let tx = pool_state.write().transactions.pop().unwrap();
+		match Extrinsic::decode(&mut &*tx).unwrap().call {
+			Call::Oracle(crate::Call::submit_report_unsigned { payload, .. }) => {
+				assert_eq!((payload.round, payload.block_number), (0, 6));
+			},
+			call => panic!("unexpected call: {:?}", call),
+		}
+	});
+}
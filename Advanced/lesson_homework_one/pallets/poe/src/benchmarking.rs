@@ -5,6 +5,7 @@ use super::*;
 #[allow(unused)]
 use crate::*;
 use frame_benchmarking::{benchmarks, whitelisted_caller, account};
+use frame_support::traits::Time;
 use frame_system::RawOrigin;
 
 fn assert_success_event<T: Config>(generic_event: <T as Config>::Event) {
@@ -16,7 +17,7 @@ fn insert_claim<T: Config>(claim: &Vec<u8>, sender: &T::AccountId) {
 		BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).expect("claim too long");
 	Proofs::<T>::insert(
 		&bounded_claim,
-		(sender.clone(), frame_system::Pallet::<T>::block_number()),
+		(sender.clone(), frame_system::Pallet::<T>::block_number(), T::Time::now()),
 	);
 }
 
@@ -29,7 +30,7 @@ benchmarks! {
 	} : _(RawOrigin::Signed(caller.clone()), claim.clone())
 	verify {
 		assert_success_event::<T>(
-			Event::ClaimCreated(caller, claim).into()
+			Event::ClaimCreated(caller, claim, T::Time::now()).into()
 		);
 	}
 
@@ -43,7 +44,7 @@ benchmarks! {
 	} : _(RawOrigin::Signed(caller.clone()), claim.clone(), dest.clone())
 	verify {
 		assert_success_event::<T>(
-			Event::ClaimTransferred(caller.clone(), dest.clone(), claim.clone()).into()
+			Event::ClaimTransferred(caller.clone(), dest.clone(), claim.clone(), T::Time::now()).into()
 		);
 	}
 
@@ -16,12 +16,22 @@ mod benchmarking;
 
 pub mod weight;
 
+pub mod migrations;
+
+/// 存证时间戳的类型，即 `pallet_timestamp` 的 `Moment`
+pub type MomentOf<T> = <<T as Config>::Time as frame_support::traits::Time>::Moment;
+
 #[frame_support::pallet]
 pub mod pallet {
 	pub use frame_support::pallet_prelude::*;
 	pub use frame_system::pallet_prelude::*;
 	pub use sp_std::prelude::*;
-	use super::WeightInfo;
+	use frame_support::traits::Time;
+	use pallet_support::events::IndexedEvents;
+	use super::{MomentOf, WeightInfo};
+
+	/// 当前的存储版本，v1 在存证中加入了时间戳
+	pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	/// 模块配置接口
@@ -38,11 +48,15 @@ pub mod pallet {
 
 		///设置权重值
 		type WeightInfo: WeightInfo;
+
+		/// 提供存证时间的时钟，一般是 pallet_timestamp
+		type Time: Time;
 	}
 
 	#[pallet::pallet]
 	//定义自己所需的存储项所需的宏
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	//定义模块所需的结构体
 	pub struct Pallet<T>(_);
 
@@ -55,19 +69,49 @@ pub mod pallet {
 		Blake2_128Concat,
 		// 新版本使用BoundedVec而不是Vec，BoundedVec长度受限的集合类型
 		BoundedVec<u8, T::MaxClaimLength>,
-		(T::AccountId, T::BlockNumber)
+		// 所有者，存证（或转移）时的区块数和时间戳
+		(T::AccountId, T::BlockNumber, MomentOf<T>)
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn claim_length_limit)]
+	//新存证的最大长度，创世时配置，不能超过 MaxClaimLength，默认等于 MaxClaimLength
+	pub type ClaimLengthLimit<T: Config> = StorageValue<_, u32, ValueQuery, T::MaxClaimLength>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// 新存证的最大长度
+		pub max_claim_length: u32,
+		pub _phantom: PhantomData<T>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { max_claim_length: T::MaxClaimLength::get(), _phantom: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(
+				self.max_claim_length <= T::MaxClaimLength::get(),
+				"max_claim_length can't exceed MaxClaimLength"
+			);
+			ClaimLengthLimit::<T>::put(self.max_claim_length);
+		}
+	}
+
 	#[pallet::event]
-	//generate_deposit 生成了一个帮助方法 deposit_event
-	//deposit_event 方便调用生成事件的宏
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	//事件通过 deposit_indexed_event 发出，存证和账户会作为 topic 写入 EventTopics
 	pub enum Event<T: Config> {
 		//创建时触发
-		ClaimCreated(T::AccountId, Vec<u8>),
+		ClaimCreated(T::AccountId, Vec<u8>, MomentOf<T>),
 		//吊销时触发
 		ClaimRevoked(T::AccountId, Vec<u8>),
-		ClaimTransferred(T::AccountId, T::AccountId, Vec<u8>),
+		//转移时触发
+		ClaimTransferred(T::AccountId, T::AccountId, Vec<u8>, MomentOf<T>),
 	}
 
 	// Errors inform users that something went wrong.
@@ -81,7 +125,22 @@ pub mod pallet {
 
 	#[pallet::hooks]
 	//定义保留函数
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::v1::migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			crate::migrations::v1::pre_migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			crate::migrations::v1::post_migrate::<T>()?;
+			Self::do_try_state()
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
@@ -94,16 +153,20 @@ pub mod pallet {
 			// 校验是否是一个签名的交易并获取sender
 			let sender = ensure_signed(origin)?;
 			// 校验claim长度
+			ensure!(claim.len() as u32 <= Self::claim_length_limit(), Error::<T>::ClaimTooLong);
 			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
 			// 确保不存在
 			ensure!(!Proofs::<T>::contains_key(&bounded_claim), Error::<T>::ProofAlreadyExist);
 			// 存储
+			let now = T::Time::now();
 			Proofs::<T>::insert(
 				&bounded_claim,
-				(sender.clone(), frame_system::Pallet::<T>::block_number())
+				(sender.clone(), frame_system::Pallet::<T>::block_number(), now)
 			);
 			// 发送一个成功的事件
-			Self::deposit_event(Event::ClaimCreated(sender, claim));
+			let topics = [T::topic(&claim), T::topic(&sender)];
+			let event = Event::ClaimCreated(sender, claim, now);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 
 			Ok(().into())
 		}
@@ -116,10 +179,12 @@ pub mod pallet {
 			let sender = ensure_signed(origin)?;
 			// 校验claim长度
 			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
-			let (owner, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
+			let (owner, _, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
 			ensure!(owner == sender, Error::<T>::NotClaimOwner);
 			Proofs::<T>::remove(&bounded_claim);
-			Self::deposit_event(Event::ClaimRevoked(sender, claim));
+			let topics = [T::topic(&claim), T::topic(&sender)];
+			let event = Event::ClaimRevoked(sender, claim);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 
 			Ok(().into())
 		}
@@ -130,14 +195,33 @@ pub mod pallet {
 			// 校验claim长度
 			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
 
-			let (owner, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
+			let (owner, _, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
 			ensure!(owner == sender, Error::<T>::NotClaimOwner);
 
-			Proofs::<T>::insert(&bounded_claim, (dest.clone(), frame_system::Pallet::<T>::block_number()));
+			let now = T::Time::now();
+			Proofs::<T>::insert(&bounded_claim, (dest.clone(), frame_system::Pallet::<T>::block_number(), now));
 			// 发送事件，声明权证转移
-			Self::deposit_event(Event::ClaimTransferred(sender,dest,claim));
+			let topics = [T::topic(&claim), T::topic(&sender), T::topic(&dest)];
+			let event = Event::ClaimTransferred(sender, dest, claim, now);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
 
 			Ok(().into())
 		}
 	}
+
+	impl<T: Config> Pallet<T> {
+		/// 存储的不变量：`ClaimLengthLimit` 不超过 `MaxClaimLength`，存证的块不晚于当前块
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			ensure!(
+				Self::claim_length_limit() <= T::MaxClaimLength::get(),
+				"ClaimLengthLimit exceeds MaxClaimLength"
+			);
+			let now = frame_system::Pallet::<T>::block_number();
+			for (_, (_, block_number, _)) in Proofs::<T>::iter() {
+				ensure!(block_number <= now, "Proof recorded in a future block");
+			}
+			Ok(())
+		}
+	}
 }
\ No newline at end of file
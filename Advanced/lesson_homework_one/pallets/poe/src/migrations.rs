@@ -0,0 +1,50 @@
+//! 存证模块的存储迁移
+
+pub mod v1 {
+	use crate::{Config, Pallet, Proofs};
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// v0 -> v1：`Proofs` 的值由 `(AccountId, BlockNumber)` 变为
+	/// `(AccountId, BlockNumber, Moment)`。
+	///
+	/// 旧存证没有记录时间，迁移后时间戳为 0，表示未知。
+	pub fn migrate<T: Config>() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut translated = 0u64;
+		Proofs::<T>::translate::<(T::AccountId, T::BlockNumber), _>(|_, (owner, block_number)| {
+			translated += 1;
+			Some((owner, block_number, Default::default()))
+		});
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+
+	/// 迁移前记录存证的数量，只统计 key，不需要解码旧格式的值
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+		use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
+		let proofs = Proofs::<T>::iter_keys().count() as u32;
+		Pallet::<T>::set_temp_storage(proofs, "poe::proofs");
+		Ok(())
+	}
+
+	/// 迁移后存储版本为 1，所有存证都能按新格式解码，数量不变
+	#[cfg(feature = "try-runtime")]
+	pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+		use frame_support::{ensure, traits::OnRuntimeUpgradeHelpersExt};
+
+		ensure!(Pallet::<T>::on_chain_storage_version() == 1, "Proofs not migrated to v1");
+		let proofs = Pallet::<T>::get_temp_storage::<u32>("poe::proofs")
+			.ok_or("Proof count not recorded before the upgrade")?;
+		ensure!(Proofs::<T>::iter().count() as u32 == proofs, "Proofs lost in the migration");
+		Ok(())
+	}
+}
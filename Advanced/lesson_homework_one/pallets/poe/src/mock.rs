@@ -18,6 +18,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		PoeModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
 	}
 );
@@ -49,10 +50,18 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<5>;
+	type WeightInfo = ();
+}
+
 impl pallet_poe::Config for Test {
 	type MaxClaimLength = ConstU32<512>;
 	type Event = Event;
 	type WeightInfo = ();
+	type Time = Timestamp;
 }
 
 // Build genesis storage according to the mock runtime. 可以对区块的初始方法进行配置
@@ -1,6 +1,12 @@
 use super::*;
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok, BoundedVec};
+use crate::{mock::{self, *}, Error};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GenesisBuild, GetStorageVersion, Hooks, StorageVersion},
+	BoundedVec,
+};
+use pallet_support::events::IndexedEvents;
 
 #[test]
 fn create_claim_works() {
@@ -8,7 +14,20 @@ fn create_claim_works() {
 		let claim = vec![0, 1];
 		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
 		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
-		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, frame_system::Pallet::<Test>::block_number())));
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, frame_system::Pallet::<Test>::block_number(), 0)));
+	})
+}
+
+#[test]
+fn create_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(1_656_000_000_000);
+		let claim = vec![0, 1];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, 1, 1_656_000_000_000)));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimCreated(1, claim, 1_656_000_000_000)));
 	})
 }
 
@@ -38,6 +57,26 @@ fn create_claim_failed_when_claim_too_long() {
 	})
 }
 
+#[test]
+fn create_claim_respects_genesis_claim_length_limit() {
+	new_test_ext().execute_with(|| {
+		// 没有配置时等于 MaxClaimLength
+		assert_eq!(PoeModule::claim_length_limit(), 512);
+	});
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { max_claim_length: 4, _phantom: Default::default() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), vec![0; 5]),
+			Error::<Test>::ClaimTooLong
+		);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0; 4]));
+	});
+}
+
 #[test]
 fn revoke_claim_works() {
 	new_test_ext().execute_with(|| {
@@ -79,6 +118,23 @@ fn transfer_claim_works() {
 	})
 }
 
+#[test]
+fn transfer_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(1_000);
+		let claim = vec![0, 3];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+		System::set_block_number(2);
+		Timestamp::set_timestamp(7_000);
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((2, 2, 7_000)));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimTransferred(1, 2, claim, 7_000)));
+	})
+}
+
 #[test]
 fn transfer_claim_when_claim_not_exist() {
 	new_test_ext().execute_with(|| {
@@ -101,3 +157,54 @@ fn transfer_claim_when_not_claim_owner() {
 		);
 	})
 }
+
+#[test]
+fn migrate_to_v1_adds_timestamp() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<PoeModule>();
+		let claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(vec![0, 4]).unwrap();
+		// 写入 v0 格式的存证
+		frame_support::storage::unhashed::put_raw(
+			&Proofs::<Test>::hashed_key_for(&claim),
+			&(1u64, 3u64).encode(),
+		);
+
+		PoeModule::on_runtime_upgrade();
+
+		assert_eq!(Proofs::<Test>::get(&claim), Some((1, 3, 0)));
+		assert_eq!(PoeModule::on_chain_storage_version(), 1);
+	})
+}
+
+#[test]
+fn claim_events_are_indexed_by_claim_and_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let alice: u64 = 1;
+		let bob: u64 = 2;
+		let claim: Vec<u8> = vec![0, 6];
+		assert_ok!(PoeModule::create_claim(Origin::signed(alice), claim.clone()));
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(alice), claim.clone(), bob));
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(bob), claim.clone()));
+
+		assert_eq!(System::event_topics(&Test::topic(&claim)), vec![(1, 0), (1, 1), (1, 2)]);
+		assert_eq!(System::event_topics(&Test::topic(&alice)), vec![(1, 0), (1, 1)]);
+		assert_eq!(System::event_topics(&Test::topic(&bob)), vec![(1, 1), (1, 2)]);
+		let other_claim: Vec<u8> = vec![0, 7];
+		assert!(System::event_topics(&Test::topic(&other_claim)).is_empty());
+	})
+}
+
+#[test]
+fn try_state_checks_proofs() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(2);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0, 1]));
+		assert_ok!(PoeModule::do_try_state());
+
+		// 存证的块晚于当前块
+		let claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(vec![0, 2]).unwrap();
+		Proofs::<Test>::insert(&claim, (1, 3, 0));
+		assert_eq!(PoeModule::do_try_state(), Err("Proof recorded in a future block"));
+	})
+}
@@ -0,0 +1,38 @@
+//! 链下工作机签名交易用的 sr25519 key。
+
+/// 在 pallet 的 `crypto` 模块中生成 `key_type` 的 sr25519 `Public`、`Signature` 和 `Pair`，
+/// 以及 runtime 配置 `AuthorityId` 用的 `$auth_id`：
+///
+/// ```ignore
+/// pub mod crypto {
+/// 	pallet_support::offchain_crypto!(super::KEY_TYPE, OracleAuthId);
+/// }
+/// ```
+#[macro_export]
+macro_rules! offchain_crypto {
+	($key_type:expr, $auth_id:ident) => {
+		use $crate::reexport::{
+			frame_system::offchain::AppCrypto,
+			sp_runtime::{
+				app_crypto::{app_crypto, sr25519},
+				traits::Verify,
+				MultiSignature, MultiSigner,
+			},
+		};
+		app_crypto!(sr25519, $key_type);
+
+		pub struct $auth_id;
+
+		impl AppCrypto<MultiSigner, MultiSignature> for $auth_id {
+			type RuntimeAppPublic = Public;
+			type GenericSignature = sr25519::Signature;
+			type GenericPublic = sr25519::Public;
+		}
+
+		impl AppCrypto<<sr25519::Signature as Verify>::Signer, sr25519::Signature> for $auth_id {
+			type RuntimeAppPublic = Public;
+			type GenericSignature = sr25519::Signature;
+			type GenericPublic = sr25519::Public;
+		}
+	};
+}
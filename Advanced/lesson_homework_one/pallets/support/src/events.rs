@@ -0,0 +1,22 @@
+//! 带 topic 的事件，客户端可以按 topic 过滤事件。
+
+use codec::Encode;
+use frame_support::sp_runtime::traits::Hash;
+
+/// 所有 runtime 都实现了的 topic 和事件辅助函数
+pub trait IndexedEvents: frame_system::Config {
+	/// 值的 topic：`hash(SCALE(value))`
+	fn topic<V: Encode + ?Sized>(value: &V) -> Self::Hash {
+		Self::Hashing::hash_of(value)
+	}
+
+	/// 发出事件，并把 `topics` 写入 `EventTopics`
+	fn deposit_indexed_event(
+		topics: &[Self::Hash],
+		event: impl Into<<Self as frame_system::Config>::Event>,
+	) {
+		frame_system::Pallet::<Self>::deposit_event_indexed(topics, event.into());
+	}
+}
+
+impl<T: frame_system::Config> IndexedEvents for T {}
@@ -0,0 +1,23 @@
+//! 从数据源返回的 JSON 中取值。
+
+use frame_support::sp_std::{str, vec::Vec};
+use serde_json::Value;
+
+/// 按 `.` 分隔的路径取出 JSON 中的标量，数组用下标，比如 `data.prices.0.usd`。
+/// 字符串返回其内容，数字和布尔值返回 JSON 文本
+pub fn json_value_at(json: &Value, path: &[u8]) -> Option<Vec<u8>> {
+	let path = str::from_utf8(path).ok()?;
+	let mut value = json;
+	for segment in path.split('.').filter(|s| !s.is_empty()) {
+		value = match value {
+			Value::Object(map) => map.get(segment)?,
+			Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+			_ => return None,
+		};
+	}
+	match value {
+		Value::String(s) => Some(s.as_bytes().to_vec()),
+		Value::Number(_) | Value::Bool(_) => serde_json::to_vec(value).ok(),
+		_ => None,
+	}
+}
@@ -0,0 +1,19 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! lesson_homework_one 和 lesson_homework_six 的 pallet 共用的辅助代码。
+
+#[cfg(test)]
+mod tests;
+
+pub mod crypto;
+pub mod events;
+pub mod json;
+pub mod prune;
+pub mod stats;
+
+/// 宏展开后使用的依赖，调用宏的 pallet 不需要自己引入
+#[doc(hidden)]
+pub mod reexport {
+	pub use frame_support::sp_runtime;
+	pub use frame_system;
+}
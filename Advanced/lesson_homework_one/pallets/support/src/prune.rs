@@ -0,0 +1,51 @@
+//! 清理按块号写入的链下存储。
+//!
+//! key 是前缀后面跟 SCALE 编码的块号，链下工作机从游标记录的块继续，清理早于保留期的 key。
+
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_runtime::{
+		offchain::storage::{StorageRetrievalError, StorageValueRef},
+		traits::AtLeast32BitUnsigned,
+	},
+	sp_std::vec::Vec,
+};
+
+/// 每次链下工作机最多清理多少个块的 key
+pub const MAX_PRUNE_PER_RUN: u32 = 64;
+
+/// `prefix` 后面跟 SCALE 编码的块号
+pub fn block_key<N: Encode>(prefix: &[u8], block_number: N) -> Vec<u8> {
+	block_number.using_encoded(|encoded| [prefix, encoded].concat())
+}
+
+/// 清理 `prefix` 下早于 `retention` 个块的 key。
+/// 从 `cursor_key` 记录的块继续，每次最多清理 `MAX_PRUNE_PER_RUN` 个块，返回清理的块的范围
+pub fn prune_block_keys<N>(
+	cursor_key: &[u8],
+	prefix: &[u8],
+	block_number: N,
+	retention: N,
+) -> Option<(N, N)>
+where
+	N: AtLeast32BitUnsigned + Copy + Encode + Decode,
+{
+	let target = block_number.saturating_sub(retention);
+	let mut from = N::zero();
+	let to = StorageValueRef::persistent(cursor_key)
+		.mutate(|cursor: Result<Option<N>, StorageRetrievalError>| {
+			from = cursor.ok().flatten().unwrap_or_else(N::zero);
+			if from >= target {
+				return Err(())
+			}
+			Ok(target.min(from.saturating_add(MAX_PRUNE_PER_RUN.into())))
+		})
+		.ok()?;
+
+	let mut n = from;
+	while n < to {
+		StorageValueRef::persistent(&block_key(prefix, n)).clear();
+		n += N::one();
+	}
+	Some((from, to))
+}
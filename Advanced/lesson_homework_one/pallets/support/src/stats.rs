@@ -0,0 +1,48 @@
+//! 链下工作机的累计运行统计。
+//!
+//! 统计保存在链下持久化存储中，每个模块一个 key，由节点读取并导出为 Prometheus 指标。
+
+use codec::{Decode, Encode};
+use frame_support::{
+	sp_runtime::offchain::storage::{StorageRetrievalError, StorageValueRef},
+	sp_std::vec::Vec,
+	RuntimeDebug,
+};
+
+/// 链下工作机的累计运行统计
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct OffchainWorkerStats {
+	/// 运行次数
+	pub runs: u64,
+	/// 最近一次运行的耗时，毫秒
+	pub last_duration_ms: u64,
+	/// 请求数据源失败的次数
+	pub http_failures: u64,
+	/// 提交到交易池的交易数
+	pub submitted: u64,
+}
+
+/// 模块的统计在链下存储中的 key，`prefix` 区分不同的模块
+pub fn stats_key(prefix: &[u8]) -> Vec<u8> {
+	[prefix, b"::ocw-stats"].concat()
+}
+
+/// `prefix` 模块的链下工作机的累计运行统计
+pub fn offchain_worker_stats(prefix: &[u8]) -> OffchainWorkerStats {
+	StorageValueRef::persistent(&stats_key(prefix))
+		.get()
+		.ok()
+		.flatten()
+		.unwrap_or_default()
+}
+
+/// 更新 `prefix` 模块的统计
+pub fn update_stats(prefix: &[u8], f: impl FnOnce(&mut OffchainWorkerStats)) {
+	let _ = StorageValueRef::persistent(&stats_key(prefix)).mutate(
+		|stats: Result<Option<OffchainWorkerStats>, StorageRetrievalError>| {
+			let mut stats = stats.ok().flatten().unwrap_or_default();
+			f(&mut stats);
+			Ok::<_, ()>(stats)
+		},
+	);
+}
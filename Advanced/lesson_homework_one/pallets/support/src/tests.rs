@@ -0,0 +1,51 @@
+use crate::{
+	json::json_value_at,
+	prune::{block_key, prune_block_keys, MAX_PRUNE_PER_RUN},
+};
+use sp_core::offchain::{testing, OffchainDbExt, OffchainWorkerExt, StorageKind};
+
+#[test]
+fn json_value_at_works() {
+	let json: serde_json::Value =
+		serde_json::from_str(r#"{"data":{"name":"dot","prices":[{"usd":1},{"usd":7.5,"ok":true}]}}"#)
+			.unwrap();
+	assert_eq!(json_value_at(&json, b"data.name"), Some(b"dot".to_vec()));
+	assert_eq!(json_value_at(&json, b"data.prices.1.usd"), Some(b"7.5".to_vec()));
+	assert_eq!(json_value_at(&json, b"data.prices.1.ok"), Some(b"true".to_vec()));
+	assert_eq!(json_value_at(&json, b"data.prices"), None);
+	assert_eq!(json_value_at(&json, b"data.prices.2.usd"), None);
+	assert_eq!(json_value_at(&json, b"data.missing"), None);
+}
+
+#[test]
+fn prune_block_keys_continues_from_cursor() {
+	const CURSOR: &[u8] = b"test::prune-cursor";
+	const PREFIX: &[u8] = b"test::block/";
+
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let mut t = sp_io::TestExternalities::default();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+
+	t.execute_with(|| {
+		for n in 0..100u64 {
+			sp_io::offchain::local_storage_set(
+				StorageKind::PERSISTENT,
+				&block_key(PREFIX, n),
+				b"v",
+			);
+		}
+		let stored = |n: u64| {
+			sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &block_key(PREFIX, n))
+				.is_some()
+		};
+
+		// 保留 10 个块，一次最多清理 MAX_PRUNE_PER_RUN 个块
+		let max = MAX_PRUNE_PER_RUN as u64;
+		assert_eq!(prune_block_keys(CURSOR, PREFIX, 100u64, 10), Some((0, max)));
+		assert!(!stored(max - 1) && stored(max));
+		assert_eq!(prune_block_keys(CURSOR, PREFIX, 100u64, 10), Some((max, 90)));
+		assert!(!stored(89) && stored(90));
+		assert_eq!(prune_block_keys(CURSOR, PREFIX, 100u64, 10), None);
+	});
+}
@@ -16,5 +16,15 @@ benchmarks! {
 		assert_eq!(Something::<T>::get(), Some(s));
 	}
 
+	set_feed_config {
+		let u in 0 .. T::MaxFeedUrlLength::get();
+		let url = vec![b'a'; u as usize];
+		let json_path = vec![b'a'; T::MaxJsonPathLength::get() as usize];
+		let interval: T::BlockNumber = 10u32.into();
+	}: _(RawOrigin::Root, url.clone(), json_path, interval)
+	verify {
+		assert_eq!(Feed::<T>::get().map(|config| config.url.to_vec()), Some(url));
+	}
+
 	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -14,6 +14,10 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+use codec::{Decode, Encode};
+use frame_support::{sp_std::vec::Vec, RuntimeDebug};
+use frame_system::offchain::{SignedPayload, SigningTypes};
+use sp_core::crypto::KeyTypeId;
 use sp_runtime::{
 	offchain::{
 		storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
@@ -22,7 +26,73 @@ use sp_runtime::{
 	traits::Zero,
 };
 
-use serde::{Deserialize, Deserializer};
+use pallet_support::{
+	json::json_value_at,
+	prune::{block_key, prune_block_keys},
+	stats::update_stats,
+};
+use serde_json::Value;
+
+/// 链下工作机签名用的 key 类型，通过 `author_insertKey` 插入
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"tmpl");
+
+pub mod crypto {
+	pallet_support::offchain_crypto!(super::KEY_TYPE, TemplateAuthId);
+}
+
+/// `submit_data_unsigned` 的数据，由授权的链下账户签名
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct DataPayload<Public, BlockNumber> {
+	/// 链下工作机运行时的块
+	pub block_number: BlockNumber,
+	pub n: u64,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for DataPayload<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// `submit_feed_value_unsigned` 的数据，由授权的链下账户签名
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, scale_info::TypeInfo)]
+pub struct FeedPayload<Public, BlockNumber> {
+	/// 链下工作机运行时的块
+	pub block_number: BlockNumber,
+	pub value: Vec<u8>,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for FeedPayload<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// 链下任务失败的原因
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum OffchainError {
+	/// 请求数据源或解析响应失败
+	FetchFailed,
+	/// 本地没有可以签名的 key
+	NoLocalAccount,
+	/// 交易提交到交易池失败
+	SubmitFailed,
+}
+
+/// 链下任务的失败记录，保存在链下持久化存储中，成功后清除
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct FailureRecord<BlockNumber> {
+	/// 连续失败的次数
+	pub attempts: u32,
+	/// 最近一次失败的原因
+	pub error: OffchainError,
+	/// 最近一次失败的块
+	pub failed_at: BlockNumber,
+	/// 在这个块之前不再重试
+	pub retry_at: BlockNumber,
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -33,47 +103,73 @@ pub mod pallet {
 	//use frame_support::inherent::Vec;
 	use sp_runtime::traits::Zero;
 	use sp_std::vec::Vec;
-	use core::{convert::TryInto, fmt};
+	use core::convert::TryInto;
 	use frame_support::sp_std;
-	use frame_system::offchain::SubmitTransaction;
-
-	#[derive(Deserialize, Encode, Decode)]
-	struct GithubInfo {
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		login: Vec<u8>,
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		blog: Vec<u8>,
-		public_repos: u32,
+	use frame_system::offchain::{
+		Account, AppCrypto, SendUnsignedTransaction, SigningTypes, Signer,
+	};
+	use sp_runtime::{
+		traits::{IdentifyAccount, One, SaturatedConversion, Saturating},
+		transaction_validity::TransactionValidityError,
+	};
+
+	/// 链下工作机拉取的数据源配置
+	#[derive(
+		Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen,
+	)]
+	#[scale_info(skip_type_params(T))]
+	#[codec(mel_bound())]
+	pub struct FeedConfig<T: Config> {
+		/// HTTP GET 的地址
+		pub url: BoundedVec<u8, T::MaxFeedUrlLength>,
+		/// 响应 JSON 中取值的路径，用 `.` 分隔，数组用下标，比如 `data.prices.0.usd`
+		pub json_path: BoundedVec<u8, T::MaxJsonPathLength>,
+		/// 每隔多少个块拉取一次
+		pub interval: T::BlockNumber,
 	}
 
-	pub fn de_string_to_bytes<'de, D>(de: D) -> Result<Vec<u8>, D::Error>
-		where
-			D: Deserializer<'de>,
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + frame_system::offchain::SendTransactionTypes<Call<Self>> + SigningTypes
 	{
-		let s: &str = Deserialize::deserialize(de)?;
-		Ok(s.as_bytes().to_vec())
-	}
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
+		/// 链下工作机签名用的 key
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 
+		/// 授权的链下账户的最大数量
+		#[pallet::constant]
+		type MaxOffchainAuthorities: Get<u32>;
 
-	impl fmt::Debug for GithubInfo {
-		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-			write!(
-				f,
-				"{{ login: {}, blog: {}, public_repos: {} }}",
-				sp_std::str::from_utf8(&self.login).map_err(|_| fmt::Error)?,
-				sp_std::str::from_utf8(&self.blog).map_err(|_| fmt::Error)?,
-				&self.public_repos
-			)
-		}
-	}
+		/// 签名数据在多少个块之后过期
+		#[pallet::constant]
+		type UnsignedLongevity: Get<Self::BlockNumber>;
 
+		/// 链下工作机每隔多少个块运行一次
+		#[pallet::constant]
+		type OffchainWorkerInterval: Get<Self::BlockNumber>;
 
-	/// Configure the pallet by specifying the parameters and types on which it depends.
-	#[pallet::config]
-	pub trait Config: frame_system::Config + frame_system::offchain::SendTransactionTypes<Call<Self>>{
-		/// Because this pallet emits events, it depends on the runtime's definition of an event.
-		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// 链下任务失败后重试间隔的上限，间隔从 `OffchainWorkerInterval` 开始每次翻倍
+		#[pallet::constant]
+		type MaxOffchainBackoff: Get<Self::BlockNumber>;
+
+		/// `derive_key` 写入的链下存储保留多少个块，更早的由链下工作机清理
+		#[pallet::constant]
+		type OffchainKeyRetention: Get<Self::BlockNumber>;
+
+		/// 数据源 URL 的最大长度
+		#[pallet::constant]
+		type MaxFeedUrlLength: Get<u32>;
+
+		/// JSON 路径的最大长度
+		#[pallet::constant]
+		type MaxJsonPathLength: Get<u32>;
+
+		/// 上链的数据的最大长度
+		#[pallet::constant]
+		type MaxFeedValueLength: Get<u32>;
 	}
 
 
@@ -89,6 +185,52 @@ pub mod pallet {
 	// https://docs.substrate.io/v3/runtime/storage#declaring-storage-items
 	pub type Something<T> = StorageValue<_, u32>;
 
+	/// 允许提交链下数据的账户，只能由 root 修改
+	#[pallet::storage]
+	#[pallet::getter(fn offchain_authorities)]
+	pub type OffchainAuthorities<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxOffchainAuthorities>, ValueQuery>;
+
+	/// 每个链下账户最近一次 `submit_data_unsigned` 签名时的块，防止重复提交
+	#[pallet::storage]
+	pub type LastDataSubmission<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub offchain_authorities: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { offchain_authorities: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let authorities: BoundedVec<_, T::MaxOffchainAuthorities> = self
+				.offchain_authorities
+				.clone()
+				.try_into()
+				.expect("too many offchain authorities in genesis");
+			<OffchainAuthorities<T>>::put(authorities);
+		}
+	}
+
+	/// 数据源配置，只能由 root 设置
+	#[pallet::storage]
+	#[pallet::getter(fn feed_config)]
+	pub type Feed<T: Config> = StorageValue<_, FeedConfig<T>>;
+
+	/// 最近一次上链的数据以及所在的块
+	#[pallet::storage]
+	#[pallet::getter(fn feed_value)]
+	pub type FeedValue<T: Config> =
+		StorageValue<_, (BoundedVec<u8, T::MaxFeedValueLength>, T::BlockNumber)>;
+
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/v3/runtime/events-and-errors
 	#[pallet::event]
@@ -97,6 +239,16 @@ pub mod pallet {
 		/// Event documentation should end with an array that provides descriptive names for event
 		/// parameters. [something, who]
 		SomethingStored(u32, T::AccountId),
+		/// 数据源配置已更新 [url, json_path, interval]
+		FeedConfigUpdated(Vec<u8>, Vec<u8>, T::BlockNumber),
+		/// 数据源配置已删除
+		FeedConfigRemoved,
+		/// 链下拉取的数据已上链 [value]
+		FeedValueStored(Vec<u8>),
+		/// 新增授权的链下账户 [who]
+		OffchainAuthorityAdded(T::AccountId),
+		/// 移除授权的链下账户 [who]
+		OffchainAuthorityRemoved(T::AccountId),
 	}
 
 	// Errors inform users that something went wrong.
@@ -106,6 +258,22 @@ pub mod pallet {
 		NoneValue,
 		/// Errors should have helpful documentation associated with them.
 		StorageOverflow,
+		/// 数据源 URL 太长
+		FeedUrlTooLong,
+		/// JSON 路径太长
+		JsonPathTooLong,
+		/// 拉取间隔不能为 0
+		ZeroFeedInterval,
+		/// 上链的数据太长
+		FeedValueTooLong,
+		/// 没有配置数据源
+		NoFeedConfig,
+		/// 已经是授权的链下账户
+		AlreadyOffchainAuthority,
+		/// 不是授权的链下账户
+		NotOffchainAuthority,
+		/// 授权的链下账户太多
+		TooManyOffchainAuthorities,
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -150,15 +318,105 @@ pub mod pallet {
 			}
 		}
 
-		#[pallet::weight(0)]
-		pub fn submit_data_unsigned(origin: OriginFor<T>, n: u64) -> DispatchResult {
+		/// 链下工作机提交数据，`payload` 由授权的链下账户签名
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn submit_data_unsigned(
+			origin: OriginFor<T>,
+			payload: DataPayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResult {
 			ensure_none(origin)?;
 
-			log::info!("in submit_data_unsigned: {:?}", n);
+			let who = payload.public.into_account();
+			log::info!("in submit_data_unsigned: {:?} from {:?}", payload.n, who);
+			<LastDataSubmission<T>>::insert(who, payload.block_number);
 
 			// Return a successful DispatchResultWithPostInfo
 			Ok(())
 		}
+
+		/// 设置链下工作机拉取的数据源
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_feed_config(
+			origin: OriginFor<T>,
+			url: Vec<u8>,
+			json_path: Vec<u8>,
+			interval: T::BlockNumber,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!interval.is_zero(), Error::<T>::ZeroFeedInterval);
+
+			let config = FeedConfig::<T> {
+				url: url.clone().try_into().map_err(|_| Error::<T>::FeedUrlTooLong)?,
+				json_path: json_path.clone().try_into().map_err(|_| Error::<T>::JsonPathTooLong)?,
+				interval,
+			};
+			<Feed<T>>::put(config);
+
+			Self::deposit_event(Event::FeedConfigUpdated(url, json_path, interval));
+			Ok(())
+		}
+
+		/// 删除数据源，链下工作机停止拉取
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn remove_feed_config(origin: OriginFor<T>) -> DispatchResult {
+			ensure_root(origin)?;
+			<Feed<T>>::kill();
+
+			Self::deposit_event(Event::FeedConfigRemoved);
+			Ok(())
+		}
+
+		/// 链下工作机提交拉取到的数据，`payload` 由授权的链下账户签名
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+		pub fn submit_feed_value_unsigned(
+			origin: OriginFor<T>,
+			payload: FeedPayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			ensure!(<Feed<T>>::exists(), Error::<T>::NoFeedConfig);
+
+			let value = payload.value;
+			let bounded: BoundedVec<u8, T::MaxFeedValueLength> =
+				value.clone().try_into().map_err(|_| Error::<T>::FeedValueTooLong)?;
+			<FeedValue<T>>::put((bounded, <frame_system::Pallet<T>>::block_number()));
+
+			Self::deposit_event(Event::FeedValueStored(value));
+			Ok(())
+		}
+
+		/// 授权一个链下账户提交数据
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_offchain_authority(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			<OffchainAuthorities<T>>::try_mutate(|authorities| {
+				ensure!(!authorities.contains(&who), Error::<T>::AlreadyOffchainAuthority);
+				authorities
+					.try_push(who.clone())
+					.map_err(|_| Error::<T>::TooManyOffchainAuthorities)
+			})?;
+
+			Self::deposit_event(Event::OffchainAuthorityAdded(who));
+			Ok(())
+		}
+
+		/// 取消一个链下账户的授权
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_offchain_authority(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			<OffchainAuthorities<T>>::try_mutate(|authorities| {
+				let index = authorities
+					.iter()
+					.position(|a| a == &who)
+					.ok_or(Error::<T>::NotOffchainAuthority)?;
+				authorities.remove(index);
+				Ok::<(), Error<T>>(())
+			})?;
+
+			Self::deposit_event(Event::OffchainAuthorityRemoved(who));
+			Ok(())
+		}
 	}
 
 	#[pallet::hooks]
@@ -166,6 +424,12 @@ pub mod pallet {
 		/// offchain_worker 链下逻辑的入口
 		fn offchain_worker(block_number: T::BlockNumber) {
 			log::info!("Hello World from offchain workers!: {:?}", block_number);
+			if !Self::offchain_worker_due(block_number) {
+				log::debug!("Skip offchain worker at {:?}", block_number);
+				return
+			}
+			let started = sp_io::offchain::timestamp();
+			Self::prune_derived_keys(block_number);
 			// /// 睡眠8秒
 			// let timeout = sp_io::offchain::timestamp()
 			// 	.add(sp_runtime::offchain::Duration::from_millis(8000));
@@ -210,7 +474,7 @@ pub mod pallet {
 
 			} else {
 				// even
-				let key = Self::derive_key(block_number - 1u32.into());
+				let key = Self::derive_key(block_number.saturating_sub(One::one()));
 				let mut val_ref = StorageValueRef::persistent(&key);
 
 				// get from db by key
@@ -222,23 +486,26 @@ pub mod pallet {
 				}
 			}
 
-			if let Ok(info) = Self::fetch_github_info() {
-
-				log::info!("Github Info: {:?}", info);
-
-			} else {
-
-				log::info!("Error while fetch github info!");
-
-			}
-
-			// 发送不签名交易
-			let value: u64 = 42;
-			let call = Call::submit_data_unsigned { n: value };
-			_ = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-				.map_err(|_| {
-					log::error!("Failed in offchain_unsigned_tx");
-				}).expect("TODO: panic message");
+			Self::with_backoff(FEED_FAILURE_KEY, block_number, || {
+				Self::fetch_and_submit_feed(block_number)
+			});
+
+			// 发送带签名数据的不签名交易
+			Self::with_backoff(DATA_FAILURE_KEY, block_number, || {
+				let value: u64 = 42;
+				Self::send_signed_payload(
+					|account| DataPayload { block_number, n: value, public: account.public.clone() },
+					|payload, signature| Call::submit_data_unsigned { payload, signature },
+				)?;
+				log::info!("Submitted data: {:?}", value);
+				Ok(())
+			});
+
+			let duration = sp_io::offchain::timestamp().diff(&started).millis();
+			update_stats(OCW_STATS_PREFIX, |stats| {
+				stats.runs += 1;
+				stats.last_duration_ms = duration;
+			});
 
 			log::info!("Leave from offchain workers!: {:?}", block_number);
 		}
@@ -257,6 +524,16 @@ pub mod pallet {
 			0
 		}
 
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
 	}
 
 	/// 校验未签名的交易
@@ -265,16 +542,40 @@ pub mod pallet {
 		type Call = Call<T>;
 
 		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
-			if let Call::submit_data_unsigned { n: _ } = call {
-				//let provide = b"submit_xxx_unsigned".to_vec();
-				ValidTransaction::with_tag_prefix("ExampleOffchainWorker")
-					.priority(10000)
-					.and_provides(1)
-					.longevity(3)
-					.propagate(true)
-					.build()
-			} else {
-				InvalidTransaction::Call.into()
+			match call {
+				Call::submit_data_unsigned { payload, signature } => {
+					let (who, longevity) =
+						Self::validate_payload(payload, signature, payload.block_number)?;
+					// 每个账户每个块只能提交一次
+					if <LastDataSubmission<T>>::get(&who)
+						.map_or(false, |last| last >= payload.block_number)
+					{
+						return InvalidTransaction::Stale.into()
+					}
+					ValidTransaction::with_tag_prefix("ExampleOffchainWorker")
+						.priority(10000)
+						.and_provides((payload.block_number, who))
+						.longevity(longevity)
+						.propagate(true)
+						.build()
+				},
+				Call::submit_feed_value_unsigned { payload, signature } => {
+					let (_, longevity) =
+						Self::validate_payload(payload, signature, payload.block_number)?;
+					// 还没到拉取间隔的数据不接受，避免每个块都写一次
+					let config = <Feed<T>>::get().ok_or(InvalidTransaction::Call)?;
+					let now = <frame_system::Pallet<T>>::block_number();
+					if !Self::feed_due(&config, now) {
+						return InvalidTransaction::Stale.into()
+					}
+					ValidTransaction::with_tag_prefix("TemplateDataFeed")
+						.priority(10000)
+						.and_provides(payload.block_number)
+						.longevity(longevity)
+						.propagate(true)
+						.build()
+				},
+				_ => InvalidTransaction::Call.into(),
 			}
 		}
 	}
@@ -284,21 +585,215 @@ pub mod pallet {
 
 		#[deny(clippy::clone_double_ref)]
 		fn derive_key(block_number: T::BlockNumber) -> Vec<u8> {
-			block_number.using_encoded(|encoded_bn| {
-				b"node-template::storage::"
-					.iter()
-					.chain(encoded_bn)
-					.copied()
-					.collect::<Vec<u8>>()
-			})
+			block_key(DERIVED_KEY_PREFIX, block_number)
 		}
 
 
-		fn fetch_github_info() -> Result<GithubInfo, http::Error> {
+		/// 校验签名数据：签名正确、来自授权的链下账户并且没有过期。
+		/// 返回签名的账户以及交易在交易池中还能存活的块数
+		fn validate_payload<P: SignedPayload<T>>(
+			payload: &P,
+			signature: &T::Signature,
+			block_number: T::BlockNumber,
+		) -> Result<(T::AccountId, u64), TransactionValidityError> {
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+				return Err(InvalidTransaction::BadProof.into())
+			}
+
+			let who = payload.public().into_account();
+			if !<OffchainAuthorities<T>>::get().contains(&who) {
+				return Err(InvalidTransaction::BadSigner.into())
+			}
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			if block_number > now {
+				return Err(InvalidTransaction::Future.into())
+			}
+			let expires_at = block_number + T::UnsignedLongevity::get();
+			if now > expires_at {
+				return Err(InvalidTransaction::Stale.into())
+			}
+
+			Ok((who, (expires_at - now).saturated_into::<u64>().max(1)))
+		}
+
+		/// 上一次上链后是否已经过了拉取间隔
+		fn feed_due(config: &FeedConfig<T>, now: T::BlockNumber) -> bool {
+			match <FeedValue<T>>::get() {
+				Some((_, at)) => now >= at.saturating_add(config.interval),
+				None => true,
+			}
+		}
+
+		/// 清理 `derive_key` 写入的、早于 `OffchainKeyRetention` 个块的链下存储。
+		/// 从上次清理到的块继续，每次最多清理 `MAX_PRUNE_PER_RUN` 个块
+		fn prune_derived_keys(block_number: T::BlockNumber) {
+			let retention = T::OffchainKeyRetention::get();
+			if let Some((from, to)) =
+				prune_block_keys(PRUNE_CURSOR_KEY, DERIVED_KEY_PREFIX, block_number, retention)
+			{
+				log::debug!("Pruned derived keys of blocks {:?}..{:?}", from, to);
+			}
+		}
+
+		/// 距离上一次运行是否已经过了 `OffchainWorkerInterval` 个块
+		fn offchain_worker_due(block_number: T::BlockNumber) -> bool {
+			let interval = T::OffchainWorkerInterval::get();
+			StorageValueRef::persistent(OCW_LAST_RUN_KEY)
+				.mutate(|last: Result<Option<T::BlockNumber>, StorageRetrievalError>| match last {
+					Ok(Some(last)) if block_number < last.saturating_add(interval) => Err(()),
+					_ => Ok(block_number),
+				})
+				.is_ok()
+		}
+
+		/// 链下持久化存储中 `key` 对应任务的失败记录
+		pub fn failure_record(key: &[u8]) -> Option<FailureRecord<T::BlockNumber>> {
+			StorageValueRef::persistent(key).get().ok().flatten()
+		}
+
+		/// 存储的不变量：授权的链下账户没有重复，数据源的拉取间隔不为 0，上链数据的块不晚于当前块
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let authorities = Self::offchain_authorities();
+			for (i, who) in authorities.iter().enumerate() {
+				ensure!(!authorities[..i].contains(who), "Duplicate offchain authority");
+			}
+			if <Feed<T>>::exists() {
+				let config = Self::feed_config().ok_or("Feed config can't be decoded")?;
+				ensure!(!config.interval.is_zero(), "Zero feed interval");
+			}
+			if let Some((_, at)) = Self::feed_value() {
+				ensure!(
+					at <= <frame_system::Pallet<T>>::block_number(),
+					"Feed value stored in a future block"
+				);
+			}
+			Ok(())
+		}
+
+		/// 第 `attempts` 次失败后的重试间隔：`OffchainWorkerInterval * 2^(attempts - 1)`，
+		/// 不超过 `MaxOffchainBackoff`
+		pub(crate) fn backoff(attempts: u32) -> T::BlockNumber {
+			let factor = 1u32.checked_shl(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+			T::OffchainWorkerInterval::get()
+				.max(One::one())
+				.saturating_mul(factor.into())
+				.min(T::MaxOffchainBackoff::get())
+		}
+
+		/// 执行链下任务 `task`。失败时记录在 `key` 下，按指数退避在之后的块重试
+		fn with_backoff(
+			key: &[u8],
+			block_number: T::BlockNumber,
+			task: impl FnOnce() -> Result<(), OffchainError>,
+		) {
+			let name = sp_std::str::from_utf8(key).unwrap_or_default();
+			let mut store = StorageValueRef::persistent(key);
+			let failure = Self::failure_record(key);
+			if let Some(failure) = &failure {
+				if block_number < failure.retry_at {
+					log::debug!("Offchain task {} backing off until {:?}", name, failure.retry_at);
+					return
+				}
+			}
+
+			match task() {
+				Ok(()) =>
+					if failure.is_some() {
+						store.clear();
+					},
+				Err(error) => {
+					let attempts = failure.map_or(1, |f| f.attempts.saturating_add(1));
+					let retry_at = block_number.saturating_add(Self::backoff(attempts));
+					log::warn!(
+						"Offchain task {} failed with {:?} ({} attempts), retry at {:?}",
+						name,
+						error,
+						attempts,
+						retry_at
+					);
+					if error == OffchainError::FetchFailed {
+						update_stats(OCW_STATS_PREFIX, |stats| stats.http_failures += 1);
+					}
+					if error == OffchainError::NoLocalAccount {
+						log::warn!("No local account for {:?}, insert one with `author_insertKey`", KEY_TYPE);
+					}
+					store.set(&FailureRecord { attempts, error, failed_at: block_number, retry_at });
+				},
+			}
+		}
+
+		/// 用本地任意一个授权 key 签名 `payload` 并提交不签名交易
+		fn send_signed_payload<P: SignedPayload<T>>(
+			payload: impl Fn(&Account<T>) -> P,
+			call: impl Fn(P, T::Signature) -> Call<T>,
+		) -> Result<(), OffchainError> {
+			match Signer::<T, T::AuthorityId>::any_account().send_unsigned_transaction(payload, call) {
+				Some((_, Ok(()))) => {
+					update_stats(OCW_STATS_PREFIX, |stats| stats.submitted += 1);
+					Ok(())
+				},
+				Some((_, Err(()))) => Err(OffchainError::SubmitFailed),
+				None => Err(OffchainError::NoLocalAccount),
+			}
+		}
+
+		/// 按链上配置拉取数据并以不签名交易提交
+		fn fetch_and_submit_feed(block_number: T::BlockNumber) -> Result<(), OffchainError> {
+			let config = match <Feed<T>>::get() {
+				Some(config) => config,
+				None => return Ok(()),
+			};
+			if !Self::feed_due(&config, block_number) {
+				return Ok(())
+			}
+
+			// 交易上链前的几个块里不重复拉取
+			let lock = StorageValueRef::persistent(FEED_LOCK_KEY);
+			let res = lock.mutate(|last: Result<Option<T::BlockNumber>, StorageRetrievalError>| {
+				match last {
+					Ok(Some(last)) if block_number < last.saturating_add(config.interval) => Err(()),
+					_ => Ok(block_number),
+				}
+			});
+			if res.is_err() {
+				return Ok(())
+			}
+
+			let res = Self::fetch_feed_value(&config)
+				.map_err(|e| {
+					log::warn!("Error while fetching feed: {:?}", e);
+					OffchainError::FetchFailed
+				})
+				.and_then(|value| {
+					log::info!("Feed value: {:?}", sp_std::str::from_utf8(&value));
+					Self::send_signed_payload(
+						|account| FeedPayload {
+							block_number,
+							value: value.clone(),
+							public: account.public.clone(),
+						},
+						|payload, signature| Call::submit_feed_value_unsigned { payload, signature },
+					)
+				});
+			if res.is_err() {
+				// 失败后由退避决定何时重试，不用等满拉取间隔
+				StorageValueRef::persistent(FEED_LOCK_KEY).clear();
+			}
+			res
+		}
+
+		/// 请求数据源，按 JSON 路径取出数据
+		pub(crate) fn fetch_feed_value(config: &FeedConfig<T>) -> Result<Vec<u8>, http::Error> {
+			let url = sp_std::str::from_utf8(&config.url).map_err(|_| {
+				log::warn!("Feed url is not UTF8");
+				http::Error::Unknown
+			})?;
+
 			// prepare for send request
 			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(8_000));
-			let request =
-				http::Request::get("https://api.github.com/orgs/substrate-developer-hub");
+			let request = http::Request::get(url);
 			let pending = request
 				.add_header("User-Agent", "Substrate-Offchain-Worker")
 				.deadline(deadline).send().map_err(|_| http::Error::IoError)?;
@@ -308,18 +803,43 @@ pub mod pallet {
 				return Err(http::Error::Unknown)
 			}
 			let body = response.body().collect::<Vec<u8>>();
-			let body_str = sp_std::str::from_utf8(&body).map_err(|_| {
-				log::warn!("No UTF8 body");
+
+			// parse the response
+			let json: Value = serde_json::from_slice(&body).map_err(|_| {
+				log::warn!("Feed response is not JSON");
 				http::Error::Unknown
 			})?;
+			let value = json_value_at(&json, &config.json_path).ok_or_else(|| {
+				log::warn!("No value at feed json path");
+				http::Error::Unknown
+			})?;
+			if value.len() > T::MaxFeedValueLength::get() as usize {
+				log::warn!("Feed value too long: {} bytes", value.len());
+				return Err(http::Error::Unknown)
+			}
 
-			// parse the response str
-			let gh_info: GithubInfo =
-				serde_json::from_str(body_str).map_err(|_| http::Error::Unknown)?;
-
-			Ok(gh_info)
+			Ok(value)
 		}
-
-
 	}
 }
+
+/// 链下存储中记录最近一次拉取数据源的块
+const FEED_LOCK_KEY: &[u8] = b"node-template::feed::last-fetch";
+
+/// `derive_key` 写入的链下存储 key 的前缀，后面跟 SCALE 编码的块号
+pub const DERIVED_KEY_PREFIX: &[u8] = b"node-template::storage::";
+
+/// 链下存储中记录 `derive_key` 已经清理到的块
+const PRUNE_CURSOR_KEY: &[u8] = b"node-template::prune-cursor";
+
+/// 链下存储中记录链下工作机最近一次运行的块
+const OCW_LAST_RUN_KEY: &[u8] = b"node-template::ocw::last-run";
+
+/// 拉取并提交数据源失败的记录
+pub const FEED_FAILURE_KEY: &[u8] = b"node-template::ocw::failure::feed";
+
+/// 提交 `submit_data_unsigned` 失败的记录
+pub const DATA_FAILURE_KEY: &[u8] = b"node-template::ocw::failure::data";
+
+/// 链下工作机运行统计的前缀，统计由 `pallet_support::stats` 保存在链下存储中
+pub const OCW_STATS_PREFIX: &[u8] = b"node-template";
@@ -1,9 +1,12 @@
 use crate as pallet_template;
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
@@ -18,7 +21,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
+		TemplateModule: pallet_template::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -49,11 +52,58 @@ impl system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+/// 测试用的签名类型，`UintAuthorityId(n)` 对应账户 `n`
+pub struct TestAuthId;
+
+impl system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+/// 授权的链下账户
+pub const AUTHORITY: u64 = 1;
+
+parameter_types! {
+	pub static OffchainWorkerInterval: u64 = 1;
+}
+
 impl pallet_template::Config for Test {
 	type Event = Event;
+	type AuthorityId = TestAuthId;
+	type MaxOffchainAuthorities = ConstU32<2>;
+	type UnsignedLongevity = ConstU64<5>;
+	type OffchainWorkerInterval = OffchainWorkerInterval;
+	type MaxOffchainBackoff = ConstU64<8>;
+	type OffchainKeyRetention = ConstU64<4>;
+	type MaxFeedUrlLength = ConstU32<256>;
+	type MaxJsonPathLength = ConstU32<64>;
+	type MaxFeedValueLength = ConstU32<32>;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	// 链下工作机本地只有授权账户的 key
+	UintAuthorityId::set_all_keys(vec![AUTHORITY]);
+
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_template::GenesisConfig::<Test> { offchain_authorities: vec![AUTHORITY] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	t.into()
 }
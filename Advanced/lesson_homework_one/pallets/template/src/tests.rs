@@ -1,5 +1,18 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, DataPayload, Error, FailureRecord, FeedPayload, OffchainError, DATA_FAILURE_KEY,
+	FEED_FAILURE_KEY,
+};
+use codec::{Decode, Encode};
+use frame_support::{assert_noop, assert_ok, sp_std, traits::Hooks, unsigned::ValidateUnsigned};
+use pallet_support::stats::{offchain_worker_stats, OffchainWorkerStats};
+use sp_core::offchain::{
+	testing, OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+};
+use sp_runtime::{
+	testing::{TestSignature, UintAuthorityId},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	DispatchError,
+};
 
 #[test]
 fn it_works_for_default_value() {
@@ -18,3 +31,479 @@ fn correct_error_for_none_value() {
 		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
 	});
 }
+
+const FEED_URL: &[u8] = b"https://api.example.com/price";
+
+fn set_feed(interval: u64) {
+	assert_ok!(TemplateModule::set_feed_config(
+		Origin::root(),
+		FEED_URL.to_vec(),
+		b"data.prices.1.usd".to_vec(),
+		interval
+	));
+}
+
+fn expect_feed_request(state: &mut testing::OffchainState, body: &[u8]) {
+	state.expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: sp_std::str::from_utf8(FEED_URL).unwrap().into(),
+		headers: vec![("User-Agent".into(), "Substrate-Offchain-Worker".into())],
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+/// `who` 签名的数据源数据
+fn feed_payload(
+	who: u64,
+	block_number: u64,
+	value: &[u8],
+) -> (FeedPayload<UintAuthorityId, u64>, TestSignature) {
+	let payload = FeedPayload { block_number, value: value.to_vec(), public: UintAuthorityId(who) };
+	let signature = TestSignature(who, payload.encode());
+	(payload, signature)
+}
+
+fn feed_call(who: u64, block_number: u64, value: &[u8]) -> crate::Call<Test> {
+	let (payload, signature) = feed_payload(who, block_number, value);
+	crate::Call::submit_feed_value_unsigned { payload, signature }
+}
+
+/// `who` 签名的 `submit_data_unsigned` 交易
+fn data_call(who: u64, block_number: u64) -> crate::Call<Test> {
+	let payload = DataPayload { block_number, n: 42, public: UintAuthorityId(who) };
+	let signature = TestSignature(who, payload.encode());
+	crate::Call::submit_data_unsigned { payload, signature }
+}
+
+fn submit_feed_value(block_number: u64, value: &[u8]) -> sp_runtime::DispatchResult {
+	let (payload, signature) = feed_payload(AUTHORITY, block_number, value);
+	TemplateModule::submit_feed_value_unsigned(Origin::none(), payload, signature)
+}
+
+fn validate(call: &crate::Call<Test>) -> Result<(), InvalidTransaction> {
+	match TemplateModule::validate_unsigned(TransactionSource::External, call) {
+		Ok(_) => Ok(()),
+		Err(sp_runtime::transaction_validity::TransactionValidityError::Invalid(e)) => Err(e),
+		Err(e) => panic!("unexpected validity error: {:?}", e),
+	}
+}
+
+/// 从交易池中取出所有提交的数据源交易
+fn submitted_feed_values(pool: &mut testing::PoolState) -> Vec<Vec<u8>> {
+	pool.transactions
+		.drain(..)
+		.filter_map(|tx| match Extrinsic::decode(&mut &*tx).unwrap().call {
+			Call::TemplateModule(crate::Call::submit_feed_value_unsigned { payload, signature }) => {
+				assert_eq!(payload.public, UintAuthorityId(AUTHORITY));
+				assert_eq!(signature, TestSignature(AUTHORITY, payload.encode()));
+				Some(payload.value)
+			},
+			_ => None,
+		})
+		.collect()
+}
+
+/// 从交易池中取出所有 `submit_data_unsigned` 交易，返回签名时的块
+fn submitted_data_blocks(pool: &mut testing::PoolState) -> Vec<u64> {
+	pool.transactions
+		.drain(..)
+		.filter_map(|tx| match Extrinsic::decode(&mut &*tx).unwrap().call {
+			Call::TemplateModule(crate::Call::submit_data_unsigned { payload, .. }) =>
+				Some(payload.block_number),
+			_ => None,
+		})
+		.collect()
+}
+
+#[test]
+fn set_feed_config_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		set_feed(10);
+
+		let config = TemplateModule::feed_config().unwrap();
+		assert_eq!(config.url.to_vec(), FEED_URL.to_vec());
+		assert_eq!(config.json_path.to_vec(), b"data.prices.1.usd".to_vec());
+		assert_eq!(config.interval, 10);
+		System::assert_last_event(
+			crate::Event::FeedConfigUpdated(FEED_URL.to_vec(), b"data.prices.1.usd".to_vec(), 10).into(),
+		);
+
+		assert_ok!(TemplateModule::remove_feed_config(Origin::root()));
+		assert!(TemplateModule::feed_config().is_none());
+	});
+}
+
+#[test]
+fn set_feed_config_fails_for_invalid_input() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::set_feed_config(Origin::signed(1), FEED_URL.to_vec(), vec![], 10),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::set_feed_config(Origin::root(), FEED_URL.to_vec(), vec![], 0),
+			Error::<Test>::ZeroFeedInterval
+		);
+		assert_noop!(
+			TemplateModule::set_feed_config(Origin::root(), vec![b'a'; 257], vec![], 10),
+			Error::<Test>::FeedUrlTooLong
+		);
+		assert_noop!(
+			TemplateModule::set_feed_config(Origin::root(), FEED_URL.to_vec(), vec![b'a'; 65], 10),
+			Error::<Test>::JsonPathTooLong
+		);
+	});
+}
+
+#[test]
+fn submit_feed_value_unsigned_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		assert_noop!(submit_feed_value(2, b"1"), Error::<Test>::NoFeedConfig);
+
+		set_feed(10);
+		assert_ok!(submit_feed_value(2, b"1.25"));
+		assert_eq!(TemplateModule::feed_value().map(|(v, at)| (v.to_vec(), at)), Some((b"1.25".to_vec(), 3)));
+		assert_noop!(submit_feed_value(2, &[b'1'; 33]), Error::<Test>::FeedValueTooLong);
+	});
+}
+
+#[test]
+fn fetch_feed_value_works() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+
+	expect_feed_request(&mut state.write(), br#"{"data":{"prices":[{"usd":1},{"usd":7.5}]}}"#);
+
+	t.execute_with(|| {
+		set_feed(10);
+		let config = TemplateModule::feed_config().unwrap();
+		assert_eq!(TemplateModule::fetch_feed_value(&config).unwrap(), b"7.5".to_vec());
+	});
+}
+
+#[test]
+fn offchain_worker_submits_feed_value_once_per_interval() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		// 没有配置数据源时不请求
+		TemplateModule::offchain_worker(1);
+		assert!(submitted_feed_values(&mut pool_state.write()).is_empty());
+
+		set_feed(10);
+		expect_feed_request(&mut state.write(), br#"{"data":{"prices":[{"usd":1},{"usd":2}]}}"#);
+		TemplateModule::offchain_worker(2);
+		assert_eq!(submitted_feed_values(&mut pool_state.write()), vec![b"2".to_vec()]);
+
+		// 交易还没上链，拉取间隔内不重复请求
+		TemplateModule::offchain_worker(3);
+		assert!(submitted_feed_values(&mut pool_state.write()).is_empty());
+
+		// 数据已上链，下一次在第 14 块
+		System::set_block_number(4);
+		assert_ok!(submit_feed_value(2, b"2"));
+		TemplateModule::offchain_worker(13);
+		assert!(submitted_feed_values(&mut pool_state.write()).is_empty());
+
+		expect_feed_request(&mut state.write(), br#"{"data":{"prices":[{"usd":1},{"usd":3}]}}"#);
+		TemplateModule::offchain_worker(14);
+		assert_eq!(submitted_feed_values(&mut pool_state.write()), vec![b"3".to_vec()]);
+	});
+}
+
+#[test]
+fn offchain_authorities_are_managed_by_root() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(TemplateModule::offchain_authorities().to_vec(), vec![AUTHORITY]);
+
+		assert_noop!(
+			TemplateModule::add_offchain_authority(Origin::signed(AUTHORITY), 2),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::add_offchain_authority(Origin::root(), AUTHORITY),
+			Error::<Test>::AlreadyOffchainAuthority
+		);
+		assert_ok!(TemplateModule::add_offchain_authority(Origin::root(), 2));
+		System::assert_last_event(crate::Event::OffchainAuthorityAdded(2).into());
+		assert_noop!(
+			TemplateModule::add_offchain_authority(Origin::root(), 3),
+			Error::<Test>::TooManyOffchainAuthorities
+		);
+
+		assert_ok!(TemplateModule::remove_offchain_authority(Origin::root(), AUTHORITY));
+		System::assert_last_event(crate::Event::OffchainAuthorityRemoved(AUTHORITY).into());
+		assert_eq!(TemplateModule::offchain_authorities().to_vec(), vec![2]);
+		assert_noop!(
+			TemplateModule::remove_offchain_authority(Origin::root(), AUTHORITY),
+			Error::<Test>::NotOffchainAuthority
+		);
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_bad_signatures_and_unknown_keys() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(5);
+		assert_eq!(validate(&data_call(AUTHORITY, 4)), Ok(()));
+
+		// 签名和数据不一致
+		let mut call = data_call(AUTHORITY, 4);
+		if let crate::Call::submit_data_unsigned { payload, .. } = &mut call {
+			payload.n = 43;
+		}
+		assert_eq!(validate(&call), Err(InvalidTransaction::BadProof));
+
+		// 签名正确但不是授权的账户
+		assert_eq!(validate(&data_call(2, 4)), Err(InvalidTransaction::BadSigner));
+		assert_ok!(TemplateModule::add_offchain_authority(Origin::root(), 2));
+		assert_eq!(validate(&data_call(2, 4)), Ok(()));
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_stale_and_future_blocks() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(10);
+		assert_eq!(validate(&data_call(AUTHORITY, 11)), Err(InvalidTransaction::Future));
+		assert_eq!(validate(&data_call(AUTHORITY, 10)), Ok(()));
+		// UnsignedLongevity 为 5
+		assert_eq!(validate(&data_call(AUTHORITY, 5)), Ok(()));
+		assert_eq!(validate(&data_call(AUTHORITY, 4)), Err(InvalidTransaction::Stale));
+		assert_eq!(validate(&feed_call(AUTHORITY, 4, b"1")), Err(InvalidTransaction::Stale));
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_duplicate_submissions_per_block() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(5);
+		let call = data_call(AUTHORITY, 4);
+		assert_eq!(validate(&call), Ok(()));
+		if let crate::Call::submit_data_unsigned { payload, signature } = call.clone() {
+			assert_ok!(TemplateModule::submit_data_unsigned(Origin::none(), payload, signature));
+		}
+		assert_eq!(crate::LastDataSubmission::<Test>::get(AUTHORITY), Some(4));
+
+		// 同一个块的数据不能重复提交，更早的块也不行
+		assert_eq!(validate(&call), Err(InvalidTransaction::Stale));
+		assert_eq!(validate(&data_call(AUTHORITY, 3)), Err(InvalidTransaction::Stale));
+		assert_eq!(validate(&data_call(AUTHORITY, 5)), Ok(()));
+	});
+}
+
+#[test]
+fn validate_unsigned_accepts_due_feed_values_only() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		assert_eq!(validate(&feed_call(AUTHORITY, 2, b"1")), Err(InvalidTransaction::Call));
+
+		set_feed(10);
+		assert_eq!(validate(&feed_call(AUTHORITY, 2, b"1")), Ok(()));
+		assert_eq!(validate(&feed_call(2, 2, b"1")), Err(InvalidTransaction::BadSigner));
+
+		assert_ok!(submit_feed_value(2, b"1"));
+		System::set_block_number(4);
+		assert_eq!(validate(&feed_call(AUTHORITY, 3, b"1")), Err(InvalidTransaction::Stale));
+
+		// 很大的间隔不会溢出
+		set_feed(u64::MAX);
+		assert_eq!(validate(&feed_call(AUTHORITY, 3, b"1")), Err(InvalidTransaction::Stale));
+	});
+}
+
+#[test]
+fn offchain_worker_runs_at_most_once_per_interval() {
+	OffchainWorkerInterval::set(3);
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		for n in 1..=7 {
+			TemplateModule::offchain_worker(n);
+		}
+		assert_eq!(submitted_data_blocks(&mut pool_state.write()), vec![1, 4, 7]);
+	});
+}
+
+#[test]
+fn backoff_doubles_up_to_max() {
+	new_test_ext().execute_with(|| {
+		let backoff: Vec<u64> = (1..=6).map(TemplateModule::backoff).collect();
+		assert_eq!(backoff, vec![1, 2, 4, 8, 8, 8]);
+		assert_eq!(TemplateModule::backoff(u32::MAX), 8);
+	});
+}
+
+#[test]
+fn failed_feed_fetch_is_retried_with_backoff() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		set_feed(10);
+		expect_feed_request(&mut state.write(), b"not json");
+		TemplateModule::offchain_worker(2);
+		assert_eq!(
+			TemplateModule::failure_record(FEED_FAILURE_KEY),
+			Some(FailureRecord {
+				attempts: 1,
+				error: OffchainError::FetchFailed,
+				failed_at: 2,
+				retry_at: 3
+			})
+		);
+
+		expect_feed_request(&mut state.write(), b"not json");
+		TemplateModule::offchain_worker(3);
+		assert_eq!(TemplateModule::failure_record(FEED_FAILURE_KEY).unwrap().retry_at, 5);
+
+		// 退避期间不请求数据源，其他任务照常运行
+		TemplateModule::offchain_worker(4);
+		assert_eq!(submitted_data_blocks(&mut pool_state.write()), vec![2, 3, 4]);
+
+		expect_feed_request(&mut state.write(), b"not json");
+		TemplateModule::offchain_worker(5);
+		let failure = TemplateModule::failure_record(FEED_FAILURE_KEY).unwrap();
+		assert_eq!((failure.attempts, failure.retry_at), (3, 9));
+		for n in 6..9 {
+			TemplateModule::offchain_worker(n);
+		}
+		assert!(submitted_feed_values(&mut pool_state.write()).is_empty());
+
+		// 成功后清除失败记录
+		expect_feed_request(&mut state.write(), br#"{"data":{"prices":[{"usd":1},{"usd":3}]}}"#);
+		TemplateModule::offchain_worker(9);
+		assert_eq!(submitted_feed_values(&mut pool_state.write()), vec![b"3".to_vec()]);
+		assert_eq!(TemplateModule::failure_record(FEED_FAILURE_KEY), None);
+	});
+}
+
+#[test]
+fn offchain_worker_records_stats() {
+	let (offchain, state) = testing::TestOffchainExt::new();
+	let (pool, _pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		set_feed(10);
+		expect_feed_request(&mut state.write(), b"not json");
+		TemplateModule::offchain_worker(2);
+		expect_feed_request(&mut state.write(), br#"{"data":{"prices":[{"usd":1},{"usd":3}]}}"#);
+		TemplateModule::offchain_worker(3);
+
+		// 两次数据提交和一次数据源提交
+		assert_eq!(
+			offchain_worker_stats(crate::OCW_STATS_PREFIX),
+			OffchainWorkerStats { runs: 2, last_duration_ms: 0, http_failures: 1, submitted: 3 }
+		);
+	});
+}
+
+#[test]
+fn missing_local_key_is_recorded_instead_of_panicking() {
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	UintAuthorityId::set_all_keys(Vec::<u64>::new());
+
+	t.execute_with(|| {
+		TemplateModule::offchain_worker(1);
+		assert_eq!(
+			TemplateModule::failure_record(DATA_FAILURE_KEY),
+			Some(FailureRecord {
+				attempts: 1,
+				error: OffchainError::NoLocalAccount,
+				failed_at: 1,
+				retry_at: 2
+			})
+		);
+		TemplateModule::offchain_worker(2);
+		assert_eq!(TemplateModule::failure_record(DATA_FAILURE_KEY).unwrap().retry_at, 4);
+		TemplateModule::offchain_worker(3);
+		assert_eq!(TemplateModule::failure_record(DATA_FAILURE_KEY).unwrap().attempts, 2);
+		assert!(submitted_data_blocks(&mut pool_state.write()).is_empty());
+
+		UintAuthorityId::set_all_keys(vec![AUTHORITY]);
+		TemplateModule::offchain_worker(4);
+		assert_eq!(submitted_data_blocks(&mut pool_state.write()), vec![4]);
+		assert_eq!(TemplateModule::failure_record(DATA_FAILURE_KEY), None);
+	});
+}
+
+#[test]
+fn offchain_worker_prunes_old_derived_keys() {
+	let (offchain, _state) = testing::TestOffchainExt::new();
+	let (pool, _pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	let key = |n: u64| [crate::DERIVED_KEY_PREFIX, &n.encode()].concat();
+	let exists = |n: u64| {
+		sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &key(n)).is_some()
+	};
+
+	t.execute_with(|| {
+		// 旧版本没有清理的 key
+		for n in 0..100 {
+			sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, &key(n), b"x");
+		}
+
+		// 保留最近 4 个块，每次最多清理 64 个块
+		TemplateModule::offchain_worker(101);
+		assert!(!exists(63));
+		assert!(exists(64));
+
+		TemplateModule::offchain_worker(103);
+		assert!(!exists(98));
+		assert!(exists(99));
+		assert!(exists(103));
+	});
+}
+
+#[test]
+fn try_state_checks_feed_and_authorities() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(3);
+		set_feed(10);
+		assert_ok!(submit_feed_value(2, b"1.25"));
+		assert_ok!(TemplateModule::do_try_state());
+
+		// 上链数据的块晚于当前块
+		System::set_block_number(2);
+		assert_eq!(TemplateModule::do_try_state(), Err("Feed value stored in a future block"));
+
+		// 重复的链下账户
+		System::set_block_number(3);
+		crate::OffchainAuthorities::<Test>::mutate(|authorities| authorities.try_push(AUTHORITY).unwrap());
+		assert_eq!(TemplateModule::do_try_state(), Err("Duplicate offchain authority"));
+	});
+}
@@ -0,0 +1,196 @@
+//! 存证凭证（claim receipt）的离线校验。
+//!
+//! 节点通过 `poe_getClaimReceipt` RPC 导出一份自包含的凭证：`Proofs` 的存储值、
+//! 对应的 trie 读证明，以及带 GRANDPA justification 的最终块头。审计方只需要
+//! 事先信任创世块哈希和 GRANDPA 权威集合，就可以在不信任节点的情况下校验存证。
+
+use codec::{Decode, Encode};
+use finality_grandpa::voter_set::VoterSet;
+use sc_finality_grandpa::GrandpaJustification;
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	hashing::{blake2_128, twox_128},
+	Bytes, H256,
+};
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::{
+	generic,
+	traits::{BlakeTwo256, Header as HeaderT},
+	OpaqueExtrinsic,
+};
+use sp_trie::StorageProof;
+
+#[cfg(test)]
+mod tests;
+
+/// 存证模块在 `construct_runtime!` 中的名字
+pub const POE_PALLET_PREFIX: &[u8] = b"PoeModule";
+/// 存证存储项的名字
+pub const PROOFS_STORAGE_PREFIX: &[u8] = b"Proofs";
+
+/// 与节点 runtime 一致的块头类型
+pub type Header = generic::Header<u32, BlakeTwo256>;
+/// 与节点 runtime 一致的（opaque）区块类型
+pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+
+/// 节点导出的存证凭证
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimReceipt {
+	/// 存证内容（hash）
+	pub claim: Bytes,
+	/// `Proofs` 中 SCALE 编码的存储值，存证不存在时为 `None`
+	pub value: Option<Bytes>,
+	/// 在 `header.state_root` 下读取该存储项的 trie 证明节点
+	pub proof: Vec<Bytes>,
+	/// 已被 GRANDPA 最终确认的块头
+	pub header: Header,
+	/// SCALE 编码的 `GrandpaJustification`，最终确认 `header`
+	pub justification: Bytes,
+	/// 导出凭证的链的创世块哈希
+	pub genesis_hash: H256,
+	/// 签出 justification 的 GRANDPA 权威集合 id
+	pub set_id: SetId,
+}
+
+/// 审计方事先信任的链信息
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedSet {
+	/// 链的创世块哈希
+	pub genesis_hash: H256,
+	/// GRANDPA 权威集合 id
+	pub set_id: SetId,
+	/// GRANDPA 权威集合及其权重
+	pub authorities: AuthorityList,
+}
+
+/// 校验通过后的存证信息
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedClaim {
+	/// 证明所在的块哈希
+	pub block_hash: H256,
+	/// 证明所在的块高
+	pub block_number: u32,
+	/// 经过证明的 `Proofs` 存储值，`None` 表示该块上存证不存在
+	pub value: Option<Vec<u8>>,
+}
+
+impl VerifiedClaim {
+	/// 用 runtime 中 `Proofs` 的值类型解码存储值
+	pub fn decode_value<V: Decode>(&self) -> Result<Option<V>, Error> {
+		self.value
+			.as_ref()
+			.map(|v| V::decode(&mut &v[..]).map_err(|_| Error::InvalidValue))
+			.transpose()
+	}
+}
+
+/// 校验失败的原因
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// 凭证来自另一条链
+	GenesisMismatch,
+	/// 凭证的权威集合 id 与信任的不一致
+	SetIdMismatch,
+	/// 信任的权威集合为空或权重非法
+	InvalidAuthoritySet,
+	/// GRANDPA justification 无法解码或没有最终确认该块头
+	InvalidJustification(String),
+	/// trie 证明与块头的状态根不匹配
+	InvalidStorageProof(String),
+	/// 凭证中的存储值与证明得出的值不一致
+	ValueMismatch,
+	/// 存储值无法按给定类型解码
+	InvalidValue,
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Error::GenesisMismatch => write!(f, "receipt belongs to a different genesis"),
+			Error::SetIdMismatch => write!(f, "receipt was signed by a different authority set"),
+			Error::InvalidAuthoritySet => write!(f, "trusted authority set is empty or invalid"),
+			Error::InvalidJustification(e) => write!(f, "invalid GRANDPA justification: {}", e),
+			Error::InvalidStorageProof(e) => write!(f, "invalid storage proof: {}", e),
+			Error::ValueMismatch => write!(f, "receipt value does not match the storage proof"),
+			Error::InvalidValue => write!(f, "storage value can not be decoded"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// `Proofs` 中存证 `claim` 的存储键：
+/// `twox128(pallet) ++ twox128(storage) ++ blake2_128(key) ++ key`
+pub fn storage_key(claim: &[u8]) -> Vec<u8> {
+	// BoundedVec<u8, _> 与 Vec<u8> 的编码一致
+	let encoded = claim.to_vec().encode();
+
+	let mut key = Vec::with_capacity(32 + 16 + encoded.len());
+	key.extend_from_slice(&twox_128(POE_PALLET_PREFIX));
+	key.extend_from_slice(&twox_128(PROOFS_STORAGE_PREFIX));
+	key.extend_from_slice(&blake2_128(&encoded));
+	key.extend_from_slice(&encoded);
+	key
+}
+
+/// 在状态根 `state_root` 下用 `proof` 读取 `key` 的值
+pub fn check_storage_proof(
+	state_root: H256,
+	proof: &[Bytes],
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, Error> {
+	let proof = StorageProof::new(proof.iter().map(|node| node.0.clone()));
+	let mut values =
+		sp_state_machine::read_proof_check::<BlakeTwo256, _>(state_root, proof, [key])
+			.map_err(|e| Error::InvalidStorageProof(format!("{:?}", e)))?;
+
+	values
+		.remove(key)
+		.ok_or_else(|| Error::InvalidStorageProof("key is not covered by the proof".into()))
+}
+
+/// 校验 `header` 是否被 `trusted` 中的权威集合最终确认
+pub fn check_finality(
+	header: &Header,
+	justification: &[u8],
+	trusted: &TrustedSet,
+) -> Result<(), Error> {
+	let voters = VoterSet::new(trusted.authorities.iter().cloned())
+		.ok_or(Error::InvalidAuthoritySet)?;
+
+	GrandpaJustification::<Block>::decode_and_verify_finalizes(
+		justification,
+		(header.hash(), *header.number()),
+		trusted.set_id,
+		&voters,
+	)
+	.map(|_| ())
+	.map_err(|e| Error::InvalidJustification(e.to_string()))
+}
+
+/// 离线校验一份存证凭证
+pub fn verify_receipt(receipt: &ClaimReceipt, trusted: &TrustedSet) -> Result<VerifiedClaim, Error> {
+	if receipt.genesis_hash != trusted.genesis_hash {
+		return Err(Error::GenesisMismatch)
+	}
+	if receipt.set_id != trusted.set_id {
+		return Err(Error::SetIdMismatch)
+	}
+
+	check_finality(&receipt.header, &receipt.justification, trusted)?;
+
+	// 存储键由审计方自行计算，不使用节点给出的任何键
+	let key = storage_key(&receipt.claim);
+	let value = check_storage_proof(receipt.header.state_root, &receipt.proof, &key)?;
+	if value.as_deref() != receipt.value.as_ref().map(|v| &v.0[..]) {
+		return Err(Error::ValueMismatch)
+	}
+
+	Ok(VerifiedClaim {
+		block_hash: receipt.header.hash(),
+		block_number: receipt.header.number,
+		value,
+	})
+}
@@ -0,0 +1,51 @@
+//! 存证凭证离线校验工具
+//!
+//! ```bash
+//! # 在节点上导出凭证
+//! curl -H "Content-Type: application/json" \
+//!     -d '{"id":1, "jsonrpc":"2.0", "method": "poe_getClaimReceipt", "params": ["0x0102"]}' \
+//!     http://localhost:9933 | jq .result > receipt.json
+//! # 离线校验
+//! poe-verifier --receipt receipt.json --trusted trusted.json
+//! ```
+//!
+//! `trusted.json` 由审计方自行维护：
+//! `{ "genesisHash": "0x..", "setId": 0, "authorities": [["5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu", 1]] }`
+
+use clap::Parser;
+use poe_verifier::{verify_receipt, ClaimReceipt, TrustedSet};
+use sp_core::hexdisplay::HexDisplay;
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, clap::Parser)]
+#[clap(about = "Verify a proof of existence claim receipt without trusting the node")]
+struct Cli {
+	/// `poe_getClaimReceipt` 返回的 JSON 凭证
+	#[clap(long, parse(from_os_str))]
+	receipt: PathBuf,
+
+	/// 信任的创世块哈希与 GRANDPA 权威集合
+	#[clap(long, parse(from_os_str))]
+	trusted: PathBuf,
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T, String> {
+	let content = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+	serde_json::from_slice(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn main() -> Result<(), String> {
+	let cli = Cli::parse();
+	let receipt: ClaimReceipt = read_json(&cli.receipt)?;
+	let trusted: TrustedSet = read_json(&cli.trusted)?;
+
+	let verified = verify_receipt(&receipt, &trusted).map_err(|e| e.to_string())?;
+
+	println!("claim:        0x{}", HexDisplay::from(&receipt.claim.0));
+	println!("block:        #{} ({:?})", verified.block_number, verified.block_hash);
+	match verified.value {
+		Some(value) => println!("proofs value: 0x{}", HexDisplay::from(&value)),
+		None => println!("proofs value: <claim does not exist>"),
+	}
+	Ok(())
+}
@@ -0,0 +1,152 @@
+use super::*;
+use finality_grandpa::{Message, Precommit, SignedPrecommit};
+use sp_core::storage::StateVersion;
+use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+use sp_keyring::Ed25519Keyring;
+use sp_state_machine::{prove_read, InMemoryBackend};
+
+type Commit = finality_grandpa::Commit<H256, u32, AuthoritySignature, AuthorityId>;
+
+fn backend_with_claim(claim: &[u8], value: &[u8]) -> InMemoryBackend<BlakeTwo256> {
+	InMemoryBackend::<BlakeTwo256>::from((
+		vec![(None, vec![(storage_key(claim), Some(value.to_vec()))])],
+		StateVersion::V1,
+	))
+}
+
+fn proof_nodes(backend: InMemoryBackend<BlakeTwo256>, key: &[u8]) -> Vec<Bytes> {
+	prove_read(backend, &[key]).unwrap().into_nodes().into_iter().map(Bytes).collect()
+}
+
+/// GRANDPA 权威集合中的 `voters` 对 `header` 的 justification。
+/// 和 `GrandpaJustification` 的编码一致：轮次、commit 以及投票的祖先块头（这里为空）
+fn justification(header: &Header, round: u64, set_id: SetId, voters: &[Ed25519Keyring]) -> Bytes {
+	let precommit = Precommit { target_hash: header.hash(), target_number: header.number };
+	let precommits = voters
+		.iter()
+		.map(|voter| {
+			let message = Message::Precommit(precommit.clone());
+			let payload = sp_finality_grandpa::localized_payload(round, set_id, &message);
+			SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: voter.sign(&payload).into(),
+				id: voter.public().into(),
+			}
+		})
+		.collect();
+	let commit = Commit { target_hash: header.hash(), target_number: header.number, precommits };
+	Bytes((round, commit, Vec::<Header>::new()).encode())
+}
+
+fn trusted_set(voters: &[Ed25519Keyring]) -> TrustedSet {
+	TrustedSet {
+		genesis_hash: H256::repeat_byte(7),
+		set_id: 1,
+		authorities: voters.iter().map(|voter| (voter.public().into(), 1)).collect(),
+	}
+}
+
+/// 在包含 `claim` 的状态上最终确认的块导出的凭证
+fn finalized_receipt(claim: &[u8], value: &[u8], voters: &[Ed25519Keyring]) -> ClaimReceipt {
+	let backend = backend_with_claim(claim, value);
+	let header = Header::new(
+		5,
+		Default::default(),
+		*backend.root(),
+		H256::repeat_byte(1),
+		Default::default(),
+	);
+	let trusted = trusted_set(voters);
+
+	ClaimReceipt {
+		claim: Bytes(claim.to_vec()),
+		value: Some(Bytes(value.to_vec())),
+		proof: proof_nodes(backend, &storage_key(claim)),
+		justification: justification(&header, 3, trusted.set_id, voters),
+		header,
+		genesis_hash: trusted.genesis_hash,
+		set_id: trusted.set_id,
+	}
+}
+
+#[test]
+fn storage_key_has_pallet_and_storage_prefix() {
+	let claim = vec![0, 1];
+	let key = storage_key(&claim);
+
+	assert_eq!(&key[..16], &twox_128(b"PoeModule"));
+	assert_eq!(&key[16..32], &twox_128(b"Proofs"));
+	assert_eq!(&key[48..], &claim.encode()[..]);
+}
+
+#[test]
+fn check_storage_proof_works() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim, b"owner");
+	let root = *backend.root();
+	let key = storage_key(&claim);
+
+	let proof = proof_nodes(backend, &key);
+	assert_eq!(check_storage_proof(root, &proof, &key), Ok(Some(b"owner".to_vec())));
+}
+
+#[test]
+fn check_storage_proof_proves_absence() {
+	let backend = backend_with_claim(&[0, 1], b"owner");
+	let root = *backend.root();
+	let key = storage_key(&[0, 2]);
+
+	let proof = proof_nodes(backend, &key);
+	assert_eq!(check_storage_proof(root, &proof, &key), Ok(None));
+}
+
+#[test]
+fn check_storage_proof_fails_for_wrong_root() {
+	let claim = vec![0, 1];
+	let backend = backend_with_claim(&claim, b"owner");
+	let key = storage_key(&claim);
+
+	let proof = proof_nodes(backend, &key);
+	assert!(matches!(
+		check_storage_proof(H256::repeat_byte(1), &proof, &key),
+		Err(Error::InvalidStorageProof(_))
+	));
+}
+
+#[test]
+fn check_finality_fails_for_empty_authority_set() {
+	let header = Header::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Default::default(),
+	);
+	let trusted = TrustedSet { genesis_hash: H256::zero(), set_id: 0, authorities: vec![] };
+
+	assert_eq!(check_finality(&header, &[], &trusted), Err(Error::InvalidAuthoritySet));
+}
+
+#[test]
+fn verify_receipt_works() {
+	let voters = [Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	let value = (42u64, 5u32).encode();
+	let receipt = finalized_receipt(&[0, 1], &value, &voters);
+
+	let verified = verify_receipt(&receipt, &trusted_set(&voters)).unwrap();
+	assert_eq!(verified.block_hash, receipt.header.hash());
+	assert_eq!(verified.block_number, 5);
+	assert_eq!(verified.decode_value::<(u64, u32)>(), Ok(Some((42, 5))));
+}
+
+#[test]
+fn verify_receipt_fails_without_supermajority() {
+	let voters = [Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	// 3 个权威中只有 2 个签名，不足 2/3 以上
+	let receipt = finalized_receipt(&[0, 1], b"owner", &voters[..2]);
+
+	assert!(matches!(
+		verify_receipt(&receipt, &trusted_set(&voters)),
+		Err(Error::InvalidJustification(_))
+	));
+}
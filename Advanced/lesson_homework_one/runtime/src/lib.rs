@@ -15,7 +15,10 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{
+		AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, NumberFor,
+		Verify,
+	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature, SaturatedConversion,
 };
@@ -36,6 +39,7 @@ pub use frame_support::{
 	},
 	StorageValue,
 };
+use frame_system::EnsureRoot;
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
@@ -53,6 +57,9 @@ pub use pallet_poe;
 /// 导入kitties模块
 pub use pallet_kitties;
 
+/// 导入预言机模块
+pub use pallet_oracle;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -108,10 +115,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 103,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -263,6 +270,40 @@ impl pallet_transaction_payment::Config for Runtime {
 	type FeeMultiplierUpdate = ();
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100_000;
+	pub const ApprovalDeposit: Balance = 500;
+	pub const MetadataDepositBase: Balance = 10_000;
+	pub const MetadataDepositPerByte: Balance = 100;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	/// 只有 root 可以创建 sufficient 资产，用来支付交易费
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type Fungibles = Assets;
+	/// 按资产的 min_balance / ExistentialDeposit 把原生代币的交易费换算成资产，
+	/// 只支持 sufficient 资产。和原生代币的交易费一样销毁
+	type OnChargeAssetTransaction = pallet_asset_tx_payment::FungiblesAdapter<
+		pallet_assets::BalanceToAssetBalance<Balances, Runtime, ConvertInto>,
+		(),
+	>;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -292,8 +333,8 @@ impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for R
 			frame_system::CheckEra::<Runtime>::from(era),
 			frame_system::CheckNonce::<Runtime>::from(nonce),
 			frame_system::CheckWeight::<Runtime>::new(),
-			//pallet_asset_tx_payment::ChargeAssetTxPayment::<Runtime>::from(tip, None),
-			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+			pallet_kitties::CheckKittyRateLimit::<Runtime>::new(),
+			pallet_asset_tx_payment::ChargeAssetTxPayment::<Runtime>::from(tip, None),
 		);
 		let raw_payload = SignedPayload::new(call, extra)
 			.map_err(|_| {
@@ -323,6 +364,18 @@ impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type Event = Event;
+	type AuthorityId = pallet_template::crypto::TemplateAuthId;
+	type MaxOffchainAuthorities = ConstU32<16>;
+	// 签名数据 10 个块后过期
+	type UnsignedLongevity = ConstU32<10>;
+	// 每个块都运行链下工作机，失败后最多等 100 个块再重试
+	type OffchainWorkerInterval = ConstU32<1>;
+	type MaxOffchainBackoff = ConstU32<100>;
+	// derive_key 写入的链下存储保留一天
+	type OffchainKeyRetention = ConstU32<DAYS>;
+	type MaxFeedUrlLength = ConstU32<256>;
+	type MaxJsonPathLength = ConstU32<64>;
+	type MaxFeedValueLength = ConstU32<64>;
 }
 
 
@@ -331,6 +384,8 @@ impl pallet_poe::Config for Runtime {
 	type MaxClaimLength = ConstU32<512>;
 	type Event = Event;
 	type WeightInfo = pallet_poe::weight::SubstrateWeight<Runtime>;
+	// 存证时记录的时间戳（毫秒）
+	type Time = Timestamp;
 }
 
 impl pallet_kitties::Config for Runtime {
@@ -344,6 +399,31 @@ impl pallet_kitties::Config for Runtime {
 	type MaxKittyIndex = ConstU32<512>;
 	type KittyPrice = ConstU128<512>;
 	type AuthorityId = pallet_kitties::crypto::KittiesAuthId;
+	/// 链下索引保留一天
+	type OffchainKeyRetention = ConstU32<DAYS>;
+	/// 每个账户 10 分钟内最多创建或孵化 5 次
+	type MaxCreationsPerWindow = ConstU32<5>;
+	type CreationWindow = ConstU32<{ 10 * MINUTES }>;
+}
+
+parameter_types! {
+	/// 报告偏离中位数 5% 以上记为异常
+	pub const OracleOutlierThreshold: Perbill = Perbill::from_percent(5);
+}
+
+impl pallet_oracle::Config for Runtime {
+	type Event = Event;
+	type AuthorityId = pallet_oracle::crypto::OracleAuthId;
+	type FeedId = u32;
+	/// 按数据源的 decimals 放大后的整数
+	type Value = u128;
+	type MaxReporters = ConstU32<16>;
+	type OutlierThreshold = OracleOutlierThreshold;
+	type UnsignedLongevity = ConstU32<10>;
+	/// 报告人不够法定人数时，一轮最多等 10 分钟
+	type RoundTimeout = ConstU32<{ 10 * MINUTES }>;
+	type MaxFeedUrlLength = ConstU32<256>;
+	type MaxJsonPathLength = ConstU32<64>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -365,6 +445,9 @@ construct_runtime!(
 		TemplateModule: pallet_template,
 		PoeModule: pallet_poe,
 		KittiesModule: pallet_kitties,
+		OracleModule: pallet_oracle,
+		Assets: pallet_assets,
+		AssetTxPayment: pallet_asset_tx_payment,
 	}
 );
 
@@ -383,7 +466,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_kitties::CheckKittyRateLimit<Runtime>,
+	pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -408,9 +492,11 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_assets, Assets]
 		[pallet_timestamp, Timestamp]
 		[pallet_template, TemplateModule]
 		[pallet_poe, PoeModule]
+		[pallet_oracle, OracleModule]
 	);
 }
 
@@ -607,7 +693,12 @@ impl_runtime_apis! {
 		}
 
 		fn execute_block_no_check(block: Block) -> Weight {
-			Executive::execute_block_no_check(block)
+			let weight = Executive::execute_block_no_check(block);
+			// 执行区块后检查自定义模块的存储不变量，和 on_runtime_upgrade 一样出错时直接停止
+			PoeModule::do_try_state().unwrap();
+			KittiesModule::do_try_state().unwrap();
+			TemplateModule::do_try_state().unwrap();
+			weight
 		}
 	}
 }
@@ -0,0 +1,138 @@
+//! 用 sufficient 资产支付 kitties 和 PoE 交易的交易费。
+//!
+//! 交易由 Alice 签名，经过 `Executive::apply_extrinsic` 执行，和节点导入区块时相同。
+
+use codec::Encode;
+use frame_support::{traits::GenesisBuild, weights::GetDispatchInfo};
+use node_template_runtime::{
+	AccountId, Assets, Balance, Balances, Call, Executive, Header, Index, KittiesModule, Runtime,
+	SignedExtra, SignedPayload, System, TransactionPayment, UncheckedExtrinsic, VERSION,
+};
+use sp_keyring::Sr25519Keyring::{self, Alice};
+use sp_runtime::{
+	generic::Era,
+	traits::Header as _,
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+
+/// 可以支付交易费的资产，min_balance 是 ExistentialDeposit 的两倍，交易费按 1:2 换算
+const FEE_ASSET: u32 = 1;
+/// 不是 sufficient 的资产
+const OTHER_ASSET: u32 = 2;
+
+const BALANCE: Balance = 1 << 60;
+
+fn alice() -> AccountId {
+	Alice.to_account_id()
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> { balances: vec![(alice(), BALANCE)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	pallet_assets::GenesisConfig::<Runtime> {
+		assets: vec![(FEE_ASSET, alice(), true, 1_000), (OTHER_ASSET, alice(), false, 1)],
+		metadata: vec![],
+		accounts: vec![(FEE_ASSET, alice(), BALANCE), (OTHER_ASSET, alice(), BALANCE)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		let genesis = System::block_hash(0);
+		Executive::initialize_block(&Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			genesis,
+			Default::default(),
+		));
+	});
+	ext
+}
+
+/// 签名交易，`asset_id` 为 `None` 时用原生代币支付交易费
+fn sign(
+	signer: Sr25519Keyring,
+	call: Call,
+	nonce: Index,
+	asset_id: Option<u32>,
+) -> UncheckedExtrinsic {
+	let genesis = System::block_hash(0);
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+		frame_system::CheckNonce::<Runtime>::from(nonce),
+		frame_system::CheckWeight::<Runtime>::new(),
+		pallet_kitties::CheckKittyRateLimit::<Runtime>::new(),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::<Runtime>::from(0, asset_id),
+	);
+	let payload = SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		((), VERSION.spec_version, VERSION.transaction_version, genesis, genesis, (), (), (), ()),
+	);
+	let signature = payload.using_encoded(|e| signer.sign(e));
+
+	UncheckedExtrinsic::new_signed(call, signer.to_account_id().into(), signature.into(), extra)
+}
+
+/// 交易用原生代币计算的交易费
+fn native_fee(xt: &UncheckedExtrinsic) -> Balance {
+	TransactionPayment::compute_fee(xt.encode().len() as u32, &xt.get_dispatch_info(), 0)
+}
+
+#[test]
+fn kitty_fee_is_paid_in_asset() {
+	new_test_ext().execute_with(|| {
+		let xt = sign(Alice, pallet_kitties::Call::create {}.into(), 0, Some(FEE_ASSET));
+		let fee = native_fee(&xt);
+
+		assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+		assert_eq!(KittiesModule::kitty_owner(0), Some(alice()));
+		assert_eq!(Assets::balance(FEE_ASSET, alice()), BALANCE - 2 * fee);
+		// 原生代币只质押了 KittyPrice，没有扣交易费
+		assert_eq!(Balances::free_balance(alice()), BALANCE - 512);
+		assert_eq!(Balances::reserved_balance(alice()), 512);
+	});
+}
+
+#[test]
+fn poe_fee_is_paid_in_asset() {
+	new_test_ext().execute_with(|| {
+		let call = pallet_poe::Call::create_claim { claim: b"claim".to_vec() };
+		let xt = sign(Alice, call.into(), 0, Some(FEE_ASSET));
+		let fee = native_fee(&xt);
+
+		assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+		assert_eq!(Assets::balance(FEE_ASSET, alice()), BALANCE - 2 * fee);
+		assert_eq!(Balances::free_balance(alice()), BALANCE);
+
+		// 不指定资产时仍然用原生代币支付
+		let call = pallet_poe::Call::revoke_claim { claim: b"claim".to_vec() };
+		let xt = sign(Alice, call.into(), 1, None);
+		let fee = native_fee(&xt);
+
+		assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+		assert_eq!(Balances::free_balance(alice()), BALANCE - fee);
+	});
+}
+
+#[test]
+fn fee_in_insufficient_asset_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let xt = sign(Alice, pallet_kitties::Call::create {}.into(), 0, Some(OTHER_ASSET));
+
+		assert_eq!(
+			Executive::apply_extrinsic(xt),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Payment))
+		);
+		assert_eq!(KittiesModule::kitty_owner(0), None);
+		assert_eq!(Assets::balance(OTHER_ASSET, alice()), BALANCE);
+	});
+}
@@ -0,0 +1,404 @@
+//! 在本机启动多个 `substrate-stencil` 验证人进程的集成测试工具。
+//!
+//! 网络的 chain spec 和 `local_testnet_config` 相同，只是验证人换成 Alice、Bob、Charlie 和 Dave，
+//! 用节点的 `generate-spec` 子命令生成；每个验证人用 `--alice` 这类参数插入 session key。
+//! 所有节点只监听 127.0.0.1，不连接任何外部服务。
+//!
+//! 节点程序默认是 `target/release/substrate-stencil`，也可以用 `SUBSTRATE_STENCIL_BIN` 指定。
+
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::client::ClientT,
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_primitives::{BlockNumber, Hash, Index};
+use node_template_runtime::{
+	Block, Call, Event, Header, Runtime, Signature, SignedExtra, SignedPayload, UncheckedExtrinsic,
+	VERSION,
+};
+use sp_core::{
+	crypto::Ss58Codec,
+	hashing::twox_128,
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::generic::{Era, SignedBlock};
+use std::{
+	fs::{self, File},
+	net::TcpListener,
+	path::{Path, PathBuf},
+	process::{Child, Command, Stdio},
+	time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// 网络中的验证人，同时是 `--alice` 这类参数的名字
+pub const VALIDATORS: [&str; 4] = ["alice", "bob", "charlie", "dave"];
+
+/// 第一个验证人的 node key 和对应的 peer id，其他节点把它作为启动节点
+const BOOTNODE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const BOOTNODE_PEER_ID: &str = "12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+
+/// 等待节点启动、出块和交易被最终确定的时间
+const TIMEOUT: Duration = Duration::from_secs(180);
+
+/// 和 `local_testnet_config` 相同，只是有四个验证人
+const GENESIS: &str = r#"
+name = "Integration Testnet"
+id = "integration_testnet"
+chain_type = "Local"
+sudo = "//Alice"
+
+[[authorities]]
+stash = "//Alice//stash"
+controller = "//Alice"
+babe = "//Alice"
+grandpa = "//Alice"
+im_online = "//Alice"
+
+[[authorities]]
+stash = "//Bob//stash"
+controller = "//Bob"
+babe = "//Bob"
+grandpa = "//Bob"
+im_online = "//Bob"
+
+[[authorities]]
+stash = "//Charlie//stash"
+controller = "//Charlie"
+babe = "//Charlie"
+grandpa = "//Charlie"
+im_online = "//Charlie"
+
+[[authorities]]
+stash = "//Dave//stash"
+controller = "//Dave"
+babe = "//Dave"
+grandpa = "//Dave"
+im_online = "//Dave"
+
+[[endowed_accounts]]
+account = "//Alice"
+
+[[endowed_accounts]]
+account = "//Bob"
+
+[[endowed_accounts]]
+account = "//Charlie"
+
+[[endowed_accounts]]
+account = "//Dave"
+
+[[endowed_accounts]]
+account = "//Eve"
+
+[[endowed_accounts]]
+account = "//Ferdie"
+"#;
+
+/// 本机的验证人网络，drop 时结束所有节点进程，测试失败时保留节点日志
+pub struct Network {
+	nodes: Vec<Node>,
+	dir: Option<TempDir>,
+}
+
+/// 一个验证人进程和连接它的 WebSocket RPC
+pub struct Node {
+	name: &'static str,
+	process: Child,
+	client: WsClient,
+}
+
+impl Network {
+	/// 生成 chain spec 并启动 `VALIDATORS` 中的所有验证人
+	pub async fn start() -> Result<Self> {
+		let binary = binary()?;
+		let dir = tempfile::tempdir()?;
+		let genesis = dir.path().join("genesis.toml");
+		let spec = dir.path().join("spec.json");
+		fs::write(&genesis, GENESIS)?;
+		let status = Command::new(&binary)
+			.arg("generate-spec")
+			.arg(&genesis)
+			.arg("--output")
+			.arg(&spec)
+			.status()?;
+		if !status.success() {
+			return Err(format!("generate-spec failed: {}", status).into())
+		}
+
+		// 启动失败时 drop 会结束已经启动的节点
+		let mut network = Self { nodes: Vec::new(), dir: Some(dir) };
+		let mut bootnode = None;
+		for name in VALIDATORS {
+			let p2p_port = free_port()?;
+			let ws_port = free_port()?;
+			let log = File::create(network.path().join(format!("{}.log", name)))?;
+
+			let mut command = Command::new(&binary);
+			command
+				.arg(format!("--{}", name))
+				.arg("--chain")
+				.arg(&spec)
+				.arg("--base-path")
+				.arg(network.path().join(name))
+				.args(["--listen-addr", &format!("/ip4/127.0.0.1/tcp/{}", p2p_port)])
+				.args(["--ws-port", &ws_port.to_string()])
+				.args(["--rpc-port", &free_port()?.to_string()])
+				.args(["--no-telemetry", "--no-prometheus", "--no-mdns"])
+				.stdout(Stdio::null())
+				.stderr(log);
+			match &bootnode {
+				Some(addr) => command.args(["--bootnodes", addr]),
+				None => command.args(["--node-key", BOOTNODE_KEY]),
+			};
+			let mut process = command.spawn()?;
+			if bootnode.is_none() {
+				bootnode =
+					Some(format!("/ip4/127.0.0.1/tcp/{}/p2p/{}", p2p_port, BOOTNODE_PEER_ID));
+			}
+
+			let client = match connect(ws_port).await {
+				Ok(client) => client,
+				Err(e) => {
+					let _ = process.kill();
+					return Err(format!(
+						"{} RPC is not available: {}, see {}",
+						name,
+						e,
+						network.path().display()
+					)
+					.into())
+				},
+			};
+			network.nodes.push(Node { name, process, client });
+		}
+
+		Ok(network)
+	}
+
+	pub fn nodes(&self) -> &[Node] {
+		&self.nodes
+	}
+
+	pub fn node(&self, index: usize) -> &Node {
+		&self.nodes[index]
+	}
+
+	/// 节点的数据目录和日志所在的临时目录
+	pub fn path(&self) -> &Path {
+		self.dir.as_ref().expect("Removed only on drop; qed").path()
+	}
+
+	/// 等待所有节点最终确定第 `number` 个块，返回它的哈希
+	pub async fn wait_for_finalized(&self, number: BlockNumber) -> Result<Hash> {
+		let deadline = Instant::now() + TIMEOUT;
+		let mut hashes = Vec::new();
+		for node in &self.nodes {
+			while node.finalized_number().await? < number {
+				if Instant::now() > deadline {
+					return Err(format!(
+						"{} didn't finalize block {}, see {}",
+						node.name,
+						number,
+						self.path().display()
+					)
+					.into())
+				}
+				tokio::time::sleep(Duration::from_secs(1)).await;
+			}
+			hashes.push(node.block_hash(number).await?);
+		}
+		if hashes.windows(2).any(|w| w[0] != w[1]) {
+			return Err(
+				format!("Validators finalized different blocks {}: {:?}", number, hashes).into()
+			)
+		}
+		Ok(hashes[0])
+	}
+
+	/// 等待每个节点都连接上其他所有验证人
+	pub async fn wait_for_peers(&self) -> Result<()> {
+		let expected = VALIDATORS.len() as u64 - 1;
+		let deadline = Instant::now() + TIMEOUT;
+		for node in &self.nodes {
+			loop {
+				let peers = node.peers().await?;
+				if peers == expected {
+					break
+				}
+				if Instant::now() > deadline {
+					return Err(format!(
+						"{} has {} peers instead of {}, see {}",
+						node.name,
+						peers,
+						expected,
+						self.path().display()
+					)
+					.into())
+				}
+				tokio::time::sleep(Duration::from_secs(1)).await;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Drop for Network {
+	fn drop(&mut self) {
+		for node in &mut self.nodes {
+			let _ = node.process.kill();
+			let _ = node.process.wait();
+		}
+		if std::thread::panicking() {
+			if let Some(dir) = self.dir.take() {
+				eprintln!("Node logs are kept in {}", dir.into_path().display());
+			}
+		}
+	}
+}
+
+impl Node {
+	pub fn name(&self) -> &str {
+		self.name
+	}
+
+	/// 已连接的节点个数
+	pub async fn peers(&self) -> Result<u64> {
+		let health: serde_json::Value = self.client.request("system_health", rpc_params![]).await?;
+		health["peers"].as_u64().ok_or_else(|| "Invalid system_health response".into())
+	}
+
+	/// 最终确定的最新块号
+	pub async fn finalized_number(&self) -> Result<BlockNumber> {
+		let hash: Hash = self.client.request("chain_getFinalizedHead", rpc_params![]).await?;
+		let header: Option<Header> =
+			self.client.request("chain_getHeader", rpc_params![hash]).await?;
+		Ok(header.ok_or("Finalized header not found")?.number)
+	}
+
+	pub async fn block_hash(&self, number: BlockNumber) -> Result<Hash> {
+		let hash: Option<Hash> =
+			self.client.request("chain_getBlockHash", rpc_params![number]).await?;
+		Ok(hash.ok_or_else(|| format!("Block {} not found", number))?)
+	}
+
+	/// 区块 `hash` 的 `System::Events`
+	pub async fn events(&self, hash: Hash) -> Result<Vec<EventRecord>> {
+		let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+		let data: Option<StorageData> =
+			self.client.request("state_getStorage", rpc_params![key, hash]).await?;
+		match data {
+			Some(data) => Ok(Vec::<EventRecord>::decode(&mut &data.0[..])?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// 由 `signer` 签名并提交 `call`，等待它被最终确定，返回这笔交易触发的事件
+	pub async fn submit(&self, signer: Sr25519Keyring, call: Call) -> Result<Vec<Event>> {
+		let genesis = self.block_hash(0).await?;
+		let nonce: Index = self
+			.client
+			.request("system_accountNextIndex", rpc_params![signer.to_account_id().to_ss58check()])
+			.await?;
+		let extrinsic = sign(signer, call, nonce, genesis).encode();
+
+		let mut number = self.finalized_number().await? + 1;
+		let _: Hash = self
+			.client
+			.request("author_submitExtrinsic", rpc_params![Bytes(extrinsic.clone())])
+			.await?;
+
+		let deadline = Instant::now() + TIMEOUT;
+		loop {
+			while number <= self.finalized_number().await? {
+				let hash = self.block_hash(number).await?;
+				let block: Option<SignedBlock<Block>> =
+					self.client.request("chain_getBlock", rpc_params![hash]).await?;
+				let extrinsics = block.ok_or("Finalized block not found")?.block.extrinsics;
+				if let Some(index) = extrinsics.iter().position(|x| x.encode() == extrinsic) {
+					let phase = frame_system::Phase::ApplyExtrinsic(index as u32);
+					return Ok(self
+						.events(hash)
+						.await?
+						.into_iter()
+						.filter(|record| record.phase == phase)
+						.map(|record| record.event)
+						.collect())
+				}
+				number += 1;
+			}
+			if Instant::now() > deadline {
+				return Err(format!("Extrinsic was not finalized by {}", self.name).into())
+			}
+			tokio::time::sleep(Duration::from_secs(1)).await;
+		}
+	}
+}
+
+/// 签名交易，使用不过期的 era，所以不需要当前块的哈希
+pub fn sign(signer: Sr25519Keyring, call: Call, nonce: Index, genesis: Hash) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+		frame_system::CheckNonce::<Runtime>::from(nonce),
+		frame_system::CheckWeight::<Runtime>::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+	);
+	let payload = SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		((), VERSION.spec_version, VERSION.transaction_version, genesis, genesis, (), (), ()),
+	);
+	let signature = payload.using_encoded(|e| signer.sign(e));
+
+	UncheckedExtrinsic::new_signed(
+		call,
+		signer.to_account_id().into(),
+		Signature::Sr25519(signature),
+		extra,
+	)
+}
+
+/// 节点程序的路径
+fn binary() -> Result<PathBuf> {
+	let path = match std::env::var_os("SUBSTRATE_STENCIL_BIN") {
+		Some(path) => PathBuf::from(path),
+		None => Path::new(env!("CARGO_MANIFEST_DIR")).join("../target/release/substrate-stencil"),
+	};
+	if !path.exists() {
+		return Err(format!(
+			"{} not found, build the node with `cargo build --release` or set SUBSTRATE_STENCIL_BIN",
+			path.display()
+		)
+		.into())
+	}
+	Ok(path)
+}
+
+/// 系统分配的空闲端口
+fn free_port() -> Result<u16> {
+	Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+/// 连接节点的 WebSocket RPC，节点启动需要一些时间
+async fn connect(port: u16) -> Result<WsClient> {
+	let url = format!("ws://127.0.0.1:{}", port);
+	let deadline = Instant::now() + TIMEOUT;
+	loop {
+		match WsClientBuilder::default().build(&url).await {
+			Ok(client) => return Ok(client),
+			Err(e) if Instant::now() > deadline => return Err(e.into()),
+			Err(_) => tokio::time::sleep(Duration::from_millis(500)).await,
+		}
+	}
+}
@@ -0,0 +1,118 @@
+//! 在四个验证人的网络上部署 `lesson_homework_five` 的 erc20 合约并调用 `transfer`。
+//!
+//! 需要先编译节点和合约，也可以用 `ERC20_WASM` 指定合约的 Wasm 文件：
+//!
+//! ```bash
+//! cargo build --release
+//! (cd ../lesson_homework_five/erc20 && cargo contract build --release)
+//! cargo test -p integration-tests --test contracts -- --ignored
+//! ```
+
+use codec::{Decode, Encode};
+use integration_tests::{Network, Result};
+use node_primitives::{AccountId, Balance};
+use node_template_runtime::Event;
+use sp_core::hashing::blake2_256;
+use sp_keyring::Sr25519Keyring::{Alice, Bob};
+use std::{env, fs, path::PathBuf};
+
+/// 每次调用的 gas 上限，部署 erc20 只需要其中一小部分
+const GAS_LIMIT: u64 = 100_000_000_000;
+
+/// 部署时给 Alice 的代币数量
+const TOTAL_SUPPLY: Balance = 1_000_000;
+
+/// erc20 的 `Transfer` 事件，是合约的第一个事件
+#[derive(Debug, PartialEq, Decode)]
+struct Transfer {
+	from: Option<AccountId>,
+	to: Option<AccountId>,
+	value: Balance,
+}
+
+fn erc20_wasm() -> Result<Vec<u8>> {
+	let path = env::var_os("ERC20_WASM").map(PathBuf::from).unwrap_or_else(|| {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("../../lesson_homework_five/erc20/target/ink/erc20.wasm")
+	});
+	fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e).into())
+}
+
+/// ink! 的 selector 是名字的 blake2-256 哈希的前四个字节
+fn selector(name: &str) -> Vec<u8> {
+	blake2_256(name.as_bytes())[..4].to_vec()
+}
+
+fn emitted_transfers(events: &[Event]) -> Vec<Transfer> {
+	events
+		.iter()
+		.filter_map(|e| match e {
+			Event::Contracts(pallet_contracts::Event::ContractEmitted { data, .. })
+				if data.first() == Some(&0) =>
+				Transfer::decode(&mut &data[1..]).ok(),
+			_ => None,
+		})
+		.collect()
+}
+
+#[tokio::test]
+#[ignore = "needs a release build of substrate-stencil"]
+async fn erc20_transfer() {
+	let code = erc20_wasm().unwrap();
+	let network = Network::start().await.unwrap();
+	network.wait_for_finalized(1).await.unwrap();
+	let node = network.node(0);
+
+	let call = pallet_contracts::Call::upload_code { code, storage_deposit_limit: None };
+	let events = node.submit(Alice, call.into()).await.unwrap();
+	let code_hash = events
+		.iter()
+		.find_map(|e| match e {
+			Event::Contracts(pallet_contracts::Event::CodeStored { code_hash }) => Some(*code_hash),
+			_ => None,
+		})
+		.expect("code is stored");
+
+	let call = pallet_contracts::Call::instantiate {
+		value: 0,
+		gas_limit: GAS_LIMIT,
+		storage_deposit_limit: None,
+		code_hash,
+		data: [selector("new"), TOTAL_SUPPLY.encode()].concat(),
+		salt: vec![],
+	};
+	let events = node.submit(Alice, call.into()).await.unwrap();
+	let contract = events
+		.iter()
+		.find_map(|e| match e {
+			Event::Contracts(pallet_contracts::Event::Instantiated { deployer, contract })
+				if *deployer == Alice.to_account_id() =>
+				Some(contract.clone()),
+			_ => None,
+		})
+		.expect("contract is instantiated");
+	assert_eq!(
+		emitted_transfers(&events),
+		vec![Transfer { from: None, to: Some(Alice.to_account_id()), value: TOTAL_SUPPLY }]
+	);
+
+	let call = pallet_contracts::Call::call {
+		dest: contract.into(),
+		value: 0,
+		gas_limit: GAS_LIMIT,
+		storage_deposit_limit: None,
+		data: [selector("transfer"), (Bob.to_account_id(), 100 as Balance).encode()].concat(),
+	};
+	let events = node.submit(Alice, call.into()).await.unwrap();
+	assert_eq!(
+		emitted_transfers(&events),
+		vec![Transfer {
+			from: Some(Alice.to_account_id()),
+			to: Some(Bob.to_account_id()),
+			value: 100
+		}]
+	);
+	assert!(events
+		.iter()
+		.any(|e| matches!(e, Event::System(frame_system::Event::ExtrinsicSuccess { .. }))));
+}
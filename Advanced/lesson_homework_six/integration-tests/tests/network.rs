@@ -0,0 +1,78 @@
+//! 四个验证人的本地网络：BABE 出块、GRANDPA 最终确定，以及转账、质押和民主提案。
+//!
+//! 测试需要先编译节点，每个测试启动自己的网络：
+//!
+//! ```bash
+//! cargo build --release
+//! cargo test -p integration-tests -- --ignored
+//! ```
+
+use integration_tests::Network;
+use node_template_runtime::{constants::currency::DOLLARS, BalancesCall, Event};
+use sp_core::H256;
+use sp_keyring::Sr25519Keyring::{Alice, Eve, Ferdie};
+
+#[tokio::test]
+#[ignore = "needs a release build of substrate-stencil"]
+async fn validators_produce_and_finalize_blocks() {
+	let network = Network::start().await.unwrap();
+
+	network.wait_for_finalized(3).await.unwrap();
+	network.wait_for_peers().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs a release build of substrate-stencil"]
+async fn transfer_is_finalized() {
+	let network = Network::start().await.unwrap();
+	network.wait_for_finalized(1).await.unwrap();
+
+	let call = BalancesCall::transfer { dest: Ferdie.to_account_id().into(), value: 10 * DOLLARS };
+	let events = network.node(1).submit(Alice, call.into()).await.unwrap();
+
+	assert!(events.contains(&Event::Balances(pallet_balances::Event::Transfer {
+		from: Alice.to_account_id(),
+		to: Ferdie.to_account_id(),
+		amount: 10 * DOLLARS,
+	})));
+	assert!(events
+		.iter()
+		.any(|e| matches!(e, Event::System(frame_system::Event::ExtrinsicSuccess { .. }))));
+}
+
+#[tokio::test]
+#[ignore = "needs a release build of substrate-stencil"]
+async fn stash_bonds() {
+	let network = Network::start().await.unwrap();
+	network.wait_for_finalized(1).await.unwrap();
+
+	let call = pallet_staking::Call::bond {
+		controller: Eve.to_account_id().into(),
+		value: 1_000 * DOLLARS,
+		payee: pallet_staking::RewardDestination::Staked,
+	};
+	let events = network.node(2).submit(Eve, call.into()).await.unwrap();
+
+	assert!(events.contains(&Event::Staking(pallet_staking::Event::Bonded(
+		Eve.to_account_id(),
+		1_000 * DOLLARS
+	))));
+}
+
+#[tokio::test]
+#[ignore = "needs a release build of substrate-stencil"]
+async fn democracy_proposal_is_recorded() {
+	let network = Network::start().await.unwrap();
+	network.wait_for_finalized(1).await.unwrap();
+
+	let call = pallet_democracy::Call::propose {
+		proposal_hash: H256::repeat_byte(1),
+		value: 100 * DOLLARS,
+	};
+	let events = network.node(3).submit(Ferdie, call.into()).await.unwrap();
+
+	assert!(events.contains(&Event::Democracy(pallet_democracy::Event::Proposed {
+		proposal_index: 0,
+		deposit: 100 * DOLLARS,
+	})));
+}
@@ -1,17 +1,16 @@
-use hex_literal::hex;
 use node_primitives::*;
 use node_template_runtime::{
 	constants::currency::*, opaque::SessionKeys, BabeConfig, BalancesConfig, CouncilConfig,
 	DemocracyConfig, ElectionsConfig, GenesisConfig, GrandpaConfig, ImOnlineConfig, MaxNominations,
 	SessionConfig, StakerStatus, StakingConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig,
-	NominationPoolsConfig,
+	NominationPoolsConfig, Treasury,
 	BABE_GENESIS_EPOCH_CONFIG, wasm_binary_unwrap,
 };
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
-use sc_service::ChainType;
+use sc_service::{config::MultiaddrWithPeerId, ChainType};
 use sc_telemetry::TelemetryEndpoints;
 use sp_consensus_babe::AuthorityId as BabeId;
-use sp_core::{crypto::UncheckedInto, sr25519, Pair, Public};
+use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::{
 	traits::{IdentifyAccount, Verify},
@@ -141,14 +140,28 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
-pub fn staging_network_config() -> ChainSpec {
-	let boot_nodes = vec![];
+/// staging 网络的验证人、提名人、质押金额和启动节点，由 `staging-keys.toml` 或 `--staging-secret`
+/// 生成，见 `staging_spec.rs`
+#[derive(Clone)]
+pub struct StagingConfig {
+	pub authorities: Vec<(AccountId, AccountId, BabeId, GrandpaId, ImOnlineId)>,
+	/// 提名人和它提名的验证人 stash
+	pub nominators: Vec<(AccountId, Vec<AccountId>)>,
+	pub root_key: AccountId,
+	pub endowed_accounts: Vec<AccountId>,
+	pub validator_bond: Balance,
+	pub nominator_bond: Balance,
+	pub boot_nodes: Vec<MultiaddrWithPeerId>,
+}
+
+pub fn staging_network_config(config: StagingConfig) -> ChainSpec {
+	let boot_nodes = config.boot_nodes.clone();
 
 	ChainSpec::from_genesis(
 		"Substrate Stencil",
 		"stencil_network",
 		ChainType::Live,
-		staging_network_config_genesis,
+		move || staging_network_config_genesis(config.clone()),
 		boot_nodes,
 		Some(
 			TelemetryEndpoints::new(vec![(STAGING_TELEMETRY_URL.to_string(), 0)])
@@ -161,103 +174,76 @@ pub fn staging_network_config() -> ChainSpec {
 	)
 }
 
-fn staging_network_config_genesis() -> GenesisConfig {
-	// for i in 1 2 3 4; do for j in stash controller; do subkey inspect "$SECRET//$i//$j"; done; done
-	// for i in 1 2 3 4; do for j in babe; do subkey --sr25519 inspect "$SECRET//$i//$j"; done; done
-	// for i in 1 2 3 4; do for j in grandpa; do subkey --ed25519 inspect "$SECRET//$i//$j"; done; done
-	// for i in 1 2 3 4; do for j in im_online; do subkey --sr25519 inspect "$SECRET//$i//$j"; done; done
-	let initial_authorities: Vec<(AccountId, AccountId, BabeId, GrandpaId, ImOnlineId)> = vec![
-		(
-			// 5Grpw9i5vNyF6pbbvw7vA8pC5Vo8GMUbG8zraLMmAn32kTNH
-			hex!["d41e0bf1d76de368bdb91896b0d02d758950969ea795b1e7154343ee210de649"].into(),
-			// 5DLMZF33f61KvPDbJU5c2dPNQZ3jJyptsacpvsDhwNS1wUuU
-			hex!["382bd29103cf3af5f7c032bbedccfb3144fe672ca2c606147974bc2984ca2b14"].into(),
-			// 5Dhd2QbrSE4dyNn3YUg8j5TY3fG7ZAWZMoRRF9KUc7VPVGmC
-			hex!["48640c12bc1b351cf4b051ac1cf7b5740765d02e34989d0a9dd935ce054ebb21"]
-				.unchecked_into(),
-			// 5C6rkxAZB437B5Bf1yS4B4qjW4HZPeBp8Kzx2Se9FLKhfyHY
-			hex!["01a474a93a0cf830fb40b1d17fd1fc7c6b4a95fa11f90345558574a72da0d4b1"]
-				.unchecked_into(),
-			// 5DscuovXyY1o7DxYroYjYgipn87eqYLyQA3HJ21Utb7TqAai
-			hex!["50041e469c63c994374a2829b0b0829213abd53be5113e751043318a9d7c0757"]
-				.unchecked_into(),
-		),
-		(
-			// 5CFDk3yCSgQ2goiaksMfRMFRS7ZU28BZqPQDeAsgZUa6FRzt
-			hex!["08050f1b6bcd4651004df427c884073652bafd54e5ca25cea69169532db2910b"].into(),
-			// 5F1ks2enazaPktQa3HURLK8GywzNZaGirovPtFvvbv91TLhJ
-			hex!["8275157f2a1d8373106cb00078a73a92a3303f3bf6eb72c3a67413bd943b020b"].into(),
-			// 5CQ7gVQj96m8y79qPCqrM291rSNREfZ1Tf2fiLPSJReWTNy2
-			hex!["0ecddcf7643a98de200b80fe7b18ebd38987fa106c5ed84fc004fa75ea4bac67"]
-				.unchecked_into(),
-			// 5FyNaMc6GaioN7K9QzPJDEtGThJ1HmcruRdgtiRxaoAwn2VD
-			hex!["acdfcce0e40406fac1a8198c623ec42ea13fc627e0274bbb6c21e0811482ce13"]
-				.unchecked_into(),
-			// 5EUhcM9WPJGvhCz1UptA7ye8TgktGqbhaeSohCkAfW76q5bS
-			hex!["6ac58683d639d3992a0090ab15f8c1dcf5a5ab7652fc9de60845441f9fc93903"]
-				.unchecked_into(),
-		),
-		(
-			// 5F6YideXfGcskpdFUczu3nZcJFmU9WKHgjjNVQjqgeVGRs66
-			hex!["861c6d95051f942bb022f13fc2125b2974933d8ab1441bfdee9855e9d8051556"].into(),
-			// 5F92x4qKNYaHtfp5Yy7kb9r6gHCHkN3YSvNuedERPHgrURTn
-			hex!["8801f479e09a78515f1badee0169864dae45648109091e29b03a7b4ea97ec018"].into(),
-			// 5CLqVJSpfAdMYW1FHygEV8iEi8XFornEcCzrhw9WmFbbp8Qp
-			hex!["0c4d9de1e313572750abe19140db56433d20e4668e09de4df81a36566a8f2528"]
-				.unchecked_into(),
-			// 5HEQh8yEv4QU7joBCKYdjJJ57qU1gDAm4Xv5QZKfFnSbXpeo
-			hex!["e493d74f9fa7568cca9dd294c9619a54c2e1b6bd3ecf3677fa7f9076b98c3fcd"]
-				.unchecked_into(),
-			// 5GUEUCusMfW9c229gyuDG6XUH9pi3Cs4EZR9STtw8opfKuS6
-			hex!["c2e2a133b23995a48ff46cc704ef61929ee4a29b5fa468e41019ac63f3694e1f"]
-				.unchecked_into(),
-		),
-		(
-			// 5FxxpyvEnE2sVujvhr6x4A4G171uv4WKSLvrUNst9M8MfdpV
-			hex!["ac8fdba5bbe008f65d0e85181daa5443c2eb492fea729a5981b2161467f8655c"].into(),
-			// 5FxFAYsTNf31D5AGbXW9ETZPUZofpreHjJkdKehidcvDt5X4
-			hex!["ac039bef73f76755d3747d711554f7fb0f16022da51483e0d600c9c7c8cbf821"].into(),
-			// 5GdjiBeMEFqTE6mWod3UqPrtkQTscRGtAcmdSbR26vGiXpwB
-			hex!["ca2245b6fa117fab9353a2031104d1d5d62e311957f375762324e65d71127465"]
-				.unchecked_into(),
-			// 5DMfkaaR4tzmarUsRMkrbnFNmVnYtYjTPFJsjvA4X15WAZZB
-			hex!["392c51bf0c08f89cb1e091782d81359475d780986968ba7f6fa60f41feda6bf7"]
-				.unchecked_into(),
-			// 5HGzdyJakxDdnERv3nvNjd6Xmz5R39NEuuJ2B3miubDY6BHD
-			hex!["e68c9a2ee25e1999a4e87906aea429f3e5f3fc8dc9cd89f423d82860c6937b2e"]
-				.unchecked_into(),
-		),
-	];
+fn staging_network_config_genesis(config: StagingConfig) -> GenesisConfig {
+	let mut endowed_accounts = config.endowed_accounts;
+	if !endowed_accounts.contains(&config.root_key) {
+		endowed_accounts.insert(0, config.root_key.clone());
+	}
 
-	// generated with secret: subkey inspect "$secret"/fir
-	let root_key: AccountId = hex![
-		// 5FemZuvaJ7wVy4S49X7Y9mj7FyTR4caQD5mZo2rL7MXQoXMi
-		"9eaf896d76b55e04616ff1e1dce7fc5e4a417967c17264728b3fd8fee3b12f3c"
-	]
-	.into();
+	staking_genesis(
+		config.authorities,
+		config.nominators,
+		config.root_key,
+		endowed_accounts,
+		config.validator_bond,
+		config.nominator_bond,
+	)
+}
 
-	let endowed_accounts: Vec<AccountId> = vec![root_key.clone()];
+/// 初始余额，验证人和提名人的质押不能超过它
+pub(crate) const ENDOWMENT: Balance = 10_000_000 * DOLLARS;
+/// 开发链和测试链的质押金额
+pub(crate) const STASH: Balance = ENDOWMENT / 1000;
+
+/// Configure initial storage state for FRAME modules.
+pub(crate) fn testnet_genesis(
+	initial_authorities: Vec<(AccountId, AccountId, BabeId, GrandpaId, ImOnlineId)>,
+	initial_nominators: Vec<AccountId>,
+	root_key: AccountId,
+	endowed_accounts: Vec<AccountId>,
+) -> GenesisConfig {
+	// nominators nominate a random set of validators.
+	let mut rng = rand::thread_rng();
+	let initial_nominators = initial_nominators
+		.into_iter()
+		.map(|x| {
+			use rand::{seq::SliceRandom, Rng};
+			let limit = (MaxNominations::get() as usize).min(initial_authorities.len());
+			let count = rng.gen::<usize>() % limit;
+			let nominations = initial_authorities
+				.as_slice()
+				.choose_multiple(&mut rng, count)
+				.into_iter()
+				.map(|choice| choice.0.clone())
+				.collect::<Vec<_>>();
+			(x, nominations)
+		})
+		.collect::<Vec<_>>();
 
-	testnet_genesis(
+	staking_genesis(
 		initial_authorities,
-		vec![],
+		initial_nominators,
 		root_key,
-		endowed_accounts
+		endowed_accounts,
+		STASH,
+		STASH,
 	)
 }
 
-/// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+/// 按给定的提名和质押金额配置创世状态
+fn staking_genesis(
 	initial_authorities: Vec<(AccountId, AccountId, BabeId, GrandpaId, ImOnlineId)>,
-	initial_nominators: Vec<AccountId>,
+	initial_nominators: Vec<(AccountId, Vec<AccountId>)>,
 	root_key: AccountId,
 	mut endowed_accounts: Vec<AccountId>,
+	validator_bond: Balance,
+	nominator_bond: Balance,
 ) -> GenesisConfig {
 	// endow all authorities and nominators.
 	initial_authorities
 		.iter()
 		.map(|x| &x.0)
-		.chain(initial_nominators.iter())
+		.chain(initial_nominators.iter().map(|x| &x.0))
 		.for_each(|x| {
 			if !endowed_accounts.contains(x) {
 				endowed_accounts.push(x.clone())
@@ -265,23 +251,11 @@ fn testnet_genesis(
 		});
 
 	// stakers: all validators and nominators.
-	const ENDOWMENT: Balance = 10_000_000 * DOLLARS;
-	const STASH: Balance = ENDOWMENT / 1000;
-	let mut rng = rand::thread_rng();
 	let stakers = initial_authorities
 		.iter()
-		.map(|x| (x.0.clone(), x.1.clone(), STASH, StakerStatus::Validator))
-		.chain(initial_nominators.iter().map(|x| {
-			use rand::{seq::SliceRandom, Rng};
-			let limit = (MaxNominations::get() as usize).min(initial_authorities.len());
-			let count = rng.gen::<usize>() % limit;
-			let nominations = initial_authorities
-				.as_slice()
-				.choose_multiple(&mut rng, count)
-				.into_iter()
-				.map(|choice| choice.0.clone())
-				.collect::<Vec<_>>();
-			(x.clone(), x.clone(), STASH, StakerStatus::Nominator(nominations))
+		.map(|x| (x.0.clone(), x.1.clone(), validator_bond, StakerStatus::Validator))
+		.chain(initial_nominators.into_iter().map(|(x, nominations)| {
+			(x.clone(), x, nominator_bond, StakerStatus::Nominator(nominations))
 		}))
 		.collect::<Vec<_>>();
 
@@ -294,7 +268,13 @@ fn testnet_genesis(
 		},
 		balances: BalancesConfig {
 			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, ENDOWMENT)).collect(),
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| (k, ENDOWMENT))
+				// fund the treasury so that approved bounties can be paid out.
+				.chain(std::iter::once((Treasury::account_id(), ENDOWMENT)))
+				.collect(),
 		},
 		session: SessionConfig {
 			keys: initial_authorities
@@ -346,5 +326,7 @@ fn testnet_genesis(
 			min_join_bond: 1 * DOLLARS,
 			..Default::default()
 		},
+		poe_module: Default::default(),
+		kitties_module: Default::default(),
 	}
 }
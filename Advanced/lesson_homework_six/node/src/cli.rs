@@ -1,4 +1,5 @@
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +8,29 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	#[clap(flatten)]
+	pub staging: crate::staging_spec::StagingParams,
+
+	/// Secret URI of the key the kitties offchain worker signs `update_kitty` with. Defaults to
+	/// the development account of `--dev`, `--alice`, ...
+	#[clap(long)]
+	pub kitty_worker_suri: Option<String>,
+
+	/// File containing the secret URI of the kitties offchain worker key.
+	#[clap(long, parse(from_os_str), conflicts_with = "kitty-worker-suri")]
+	pub kitty_worker_key_file: Option<PathBuf>,
+}
+
+impl Cli {
+	/// The secret URI of the kitties offchain worker key given on the command line, if any.
+	pub fn kitty_worker_suri(&self) -> sc_cli::Result<Option<String>> {
+		match (&self.kitty_worker_suri, &self.kitty_worker_key_file) {
+			(Some(suri), _) => Ok(Some(suri.clone())),
+			(None, Some(path)) => Ok(Some(std::fs::read_to_string(path)?.trim().to_string())),
+			(None, None) => Ok(None),
+		}
+	}
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -48,4 +72,10 @@ pub enum Subcommand {
 	/// Try some command against runtime state. Note: `try-runtime` feature must be enabled.
 	#[cfg(not(feature = "try-runtime"))]
 	TryRuntime,
+
+	/// Generate a raw chain spec from a TOML or JSON genesis file.
+	GenerateSpec(crate::generate_spec::GenerateSpecCmd),
+
+	/// Insert the session keys of a validator from the session-keys directory into the keystore.
+	InsertSessionKeys(crate::insert_session_keys::InsertSessionKeysCmd),
 }
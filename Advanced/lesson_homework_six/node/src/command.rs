@@ -40,7 +40,7 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
-			"staging" => Box::new(chain_spec::staging_network_config()),
+			"staging" => Box::new(self.staging.chain_spec()?),
 			path =>
 				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
 		})
@@ -57,6 +57,8 @@ pub fn run() -> sc_cli::Result<()> {
 
 	match &cli.subcommand {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
+		Some(Subcommand::GenerateSpec(cmd)) => cmd.run(),
+		Some(Subcommand::InsertSessionKeys(cmd)) => cmd.run(&cli),
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
@@ -168,8 +170,9 @@ pub fn run() -> sc_cli::Result<()> {
 			.into()),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node_until_exit(|config| async move {
-				new_full(config).map_err(sc_cli::Error::Service)
+			let kitty_worker_suri = cli.kitty_worker_suri()?;
+			runner.run_node_until_exit(move |config| async move {
+				new_full(config, kitty_worker_suri).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
@@ -0,0 +1,321 @@
+//! `generate-spec` 子命令：从创世文件生成 raw chain spec，不需要为新网络重新编译节点。
+//!
+//! ```bash
+//! substrate-stencil generate-spec ./staging.toml --output ./staging-raw.json
+//! substrate-stencil --chain ./staging-raw.json --validator
+//! ```
+//!
+//! 创世文件可以是 TOML 或 JSON（按扩展名区分），账户和公钥使用 SS58 地址，
+//! 开发用的 `//Alice` 这类 secret URI 也可以直接写。TOML 的整数放不下较大的余额，
+//! 余额也可以写成字符串。创世 kitty 的所有者要有足够的余额质押每个 kitty 的 `KittyPrice`：
+//!
+//! ```toml
+//! name = "Stencil Staging"
+//! id = "stencil_staging"
+//! chain_type = "Live"
+//! sudo = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! nominators = ["//Dave"]
+//!
+//! [[authorities]]
+//! stash = "5GNJqTPyNqANBkUVMN1LPPrxXnFouWXoe2wNSmmEoLctxiZY"
+//! controller = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! babe = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+//! im_online = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//!
+//! [[endowed_accounts]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! balance = "1_000_000_000_000_000_000_000"
+//!
+//! [poe]
+//! max_claim_length = 256
+//!
+//! [treasury]
+//! balance = 1_000_000_000_000_000_000
+//!
+//! [[kitties]]
+//! owner = "//Alice"
+//! dna = "0x000102030405060708090a0b0c0d0e0f"
+//! ```
+
+use crate::chain_spec::{testnet_genesis, ChainSpec};
+use node_primitives::{AccountId, Balance};
+use node_template_runtime::{pallet_kitties, pallet_poe, GenesisConfig, Runtime, Treasury};
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use sc_cli::Result;
+use sc_service::ChainType;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use sp_consensus_babe::AuthorityId as BabeId;
+use sp_core::{bytes::from_hex, crypto::Ss58Codec, ed25519, sr25519, Get, Pair};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// `generate-spec`
+#[derive(Debug, clap::Parser)]
+pub struct GenerateSpecCmd {
+	/// 创世文件，`.toml` 或 `.json`
+	#[clap(parse(from_os_str))]
+	genesis: PathBuf,
+
+	/// 输出的 raw chain spec，默认打印到标准输出
+	#[clap(long, short, parse(from_os_str))]
+	output: Option<PathBuf>,
+}
+
+/// 创世文件
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GenesisFile {
+	/// 链的名字
+	name: String,
+	/// 链的 id，也是默认的数据目录名
+	id: String,
+	/// `Development`、`Local` 或 `Live`
+	#[serde(default)]
+	chain_type: ChainType,
+	/// 验证人，stash 和 controller 会被分配初始余额并质押
+	authorities: Vec<AuthorityEntry>,
+	/// 随机提名验证人的账户
+	#[serde(default)]
+	nominators: Vec<String>,
+	/// 初始余额，没有写 `balance` 的账户和开发链一样
+	#[serde(default)]
+	endowed_accounts: Vec<EndowmentEntry>,
+	/// sudo 账户
+	sudo: String,
+	#[serde(default)]
+	poe: PoeEntry,
+	#[serde(default)]
+	treasury: TreasuryEntry,
+	/// 创世时的 kitty
+	#[serde(default)]
+	kitties: Vec<KittyEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AuthorityEntry {
+	stash: String,
+	controller: String,
+	/// sr25519 公钥
+	babe: String,
+	/// ed25519 公钥
+	grandpa: String,
+	/// sr25519 公钥
+	im_online: String,
+}
+
+impl AuthorityEntry {
+	/// stash、controller 和三个 session key
+	pub(crate) fn keys(
+		&self,
+	) -> std::result::Result<(AccountId, AccountId, BabeId, GrandpaId, ImOnlineId), String> {
+		Ok((
+			account(&self.stash)?,
+			account(&self.controller)?,
+			public::<sr25519::Pair>(&self.babe)?.into(),
+			public::<ed25519::Pair>(&self.grandpa)?.into(),
+			public::<sr25519::Pair>(&self.im_online)?.into(),
+		))
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndowmentEntry {
+	account: String,
+	#[serde(default, deserialize_with = "balance")]
+	balance: Option<Balance>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TreasuryEntry {
+	/// 国库的初始余额，用于支付赏金，默认和开发链相同
+	#[serde(default, deserialize_with = "balance")]
+	balance: Option<Balance>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PoeEntry {
+	/// 新存证的最大长度，不能超过 runtime 的 `MaxClaimLength`
+	max_claim_length: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KittyEntry {
+	owner: String,
+	/// 16 字节的 dna，十六进制
+	dna: String,
+}
+
+impl GenerateSpecCmd {
+	/// 执行子命令
+	pub fn run(&self) -> Result<()> {
+		let text = fs::read_to_string(&self.genesis)?;
+		let file: GenesisFile = match self.genesis.extension().and_then(|ext| ext.to_str()) {
+			Some("toml") =>
+				toml::from_str(&text).map_err(|e| format!("Invalid genesis file: {}", e))?,
+			_ => serde_json::from_str(&text).map_err(|e| format!("Invalid genesis file: {}", e))?,
+		};
+
+		let spec = file.into_chain_spec()?;
+		let json = sc_service::chain_ops::build_spec(&spec, true)?;
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+		Ok(())
+	}
+}
+
+impl GenesisFile {
+	/// 按创世文件生成 chain spec，没有写到的部分和 `testnet_genesis` 相同
+	fn into_chain_spec(self) -> std::result::Result<ChainSpec, String> {
+		let authorities = self
+			.authorities
+			.iter()
+			.map(AuthorityEntry::keys)
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+		if authorities.is_empty() {
+			return Err("At least one authority is required".into())
+		}
+		let nominators = self
+			.nominators
+			.iter()
+			.map(|n| account(n))
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+		let sudo = account(&self.sudo)?;
+		let endowments = self
+			.endowed_accounts
+			.iter()
+			.map(|e| Ok((account(&e.account)?, e.balance)))
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+		let max_claim_length = self.poe.max_claim_length;
+		let treasury_balance = self.treasury.balance;
+		let kitties = self
+			.kitties
+			.iter()
+			.map(|k| Ok((account(&k.owner)?, dna(&k.dna)?)))
+			.collect::<std::result::Result<Vec<_>, String>>()?;
+
+		// 在 chain spec 里检查，避免生成 raw spec 时在创世构建中 panic
+		if let Some(len) = max_claim_length {
+			let max = <Runtime as pallet_poe::Config>::MaxClaimLength::get();
+			if len > max {
+				return Err(format!("poe.max_claim_length can't exceed MaxClaimLength ({})", max))
+			}
+		}
+
+		let build_genesis = move || {
+			let mut genesis = testnet_genesis(
+				authorities.clone(),
+				nominators.clone(),
+				sudo.clone(),
+				endowments.iter().map(|(who, _)| who.clone()).collect(),
+			);
+			for (who, balance) in endowments.iter().filter_map(|(w, b)| Some((w, (*b)?))) {
+				for entry in genesis.balances.balances.iter_mut().filter(|(w, _)| w == who) {
+					entry.1 = balance;
+				}
+			}
+			if let Some(balance) = treasury_balance {
+				let treasury = Treasury::account_id();
+				for entry in genesis.balances.balances.iter_mut().filter(|(w, _)| *w == treasury) {
+					entry.1 = balance;
+				}
+			}
+			if let Some(len) = max_claim_length {
+				genesis.poe_module.max_claim_length = len;
+			}
+			genesis.kitties_module.kitties = kitties.clone();
+			genesis
+		};
+		check_kitty_owners(&build_genesis())?;
+
+		Ok(ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			self.chain_type,
+			build_genesis,
+			vec![],
+			None,
+			None,
+			None,
+			None,
+			Default::default(),
+		))
+	}
+}
+
+/// 创世 kitty 的所有者要有足够的初始余额质押 `KittyPrice`，避免在创世构建中 panic
+fn check_kitty_owners(genesis: &GenesisConfig) -> std::result::Result<(), String> {
+	let price = <Runtime as pallet_kitties::Config>::KittyPrice::get();
+	let mut reserved = BTreeMap::<&AccountId, Balance>::new();
+	for (owner, _) in &genesis.kitties_module.kitties {
+		let amount = reserved.entry(owner).or_default();
+		*amount = amount.saturating_add(price);
+	}
+	for (owner, amount) in reserved {
+		let balance = genesis
+			.balances
+			.balances
+			.iter()
+			.find(|(who, _)| who == owner)
+			.map_or(0, |(_, balance)| *balance);
+		if balance < amount {
+			return Err(format!(
+				"Kitty owner {} must be endowed with at least KittyPrice ({}) per kitty",
+				owner, price
+			))
+		}
+	}
+	Ok(())
+}
+
+/// 余额可以写成整数或字符串，TOML 的整数是 i64，放不下较大的余额
+fn balance<'de, D: Deserializer<'de>>(
+	deserializer: D,
+) -> std::result::Result<Option<Balance>, D::Error> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Value {
+		Integer(u64),
+		String(String),
+	}
+
+	match Option::<Value>::deserialize(deserializer)? {
+		None => Ok(None),
+		Some(Value::Integer(n)) => Ok(Some(n.into())),
+		Some(Value::String(s)) => s
+			.replace('_', "")
+			.parse()
+			.map(Some)
+			.map_err(|e| D::Error::custom(format!("Invalid balance {}: {}", s, e))),
+	}
+}
+
+/// SS58 地址或 `//Alice` 这类 secret URI 对应的公钥
+pub(crate) fn public<P: Pair>(s: &str) -> std::result::Result<P::Public, String>
+where
+	P::Public: Ss58Codec,
+{
+	if s.starts_with("//") {
+		P::from_string(s, None)
+			.map(|pair| pair.public())
+			.map_err(|e| format!("Invalid secret URI {}: {:?}", s, e))
+	} else {
+		P::Public::from_ss58check(s).map_err(|e| format!("Invalid SS58 address {}: {:?}", s, e))
+	}
+}
+
+pub(crate) fn account(s: &str) -> std::result::Result<AccountId, String> {
+	public::<sr25519::Pair>(s).map(Into::into)
+}
+
+fn dna(s: &str) -> std::result::Result<[u8; 16], String> {
+	let bytes = from_hex(s).map_err(|e| format!("Invalid dna {}: {}", s, e))?;
+	bytes.try_into().map_err(|_| format!("Kitty dna must be 16 bytes: {}", s))
+}
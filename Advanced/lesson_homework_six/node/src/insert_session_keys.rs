@@ -0,0 +1,162 @@
+//! `insert-session-keys` 子命令：把 `session-keys` 目录中第 N 个验证人的 BABE、GRANDPA 和
+//! im-online key 插入节点的 keystore，不需要再手动调用 `author_insertKey`。
+//!
+//! ```bash
+//! substrate-stencil insert-session-keys --chain staging --base-path /tmp/validator1 \
+//!     --dir session-keys --index 1
+//! ```
+//!
+//! 目录中的 `babe{N}`、`gran{N}` 和 `imon{N}` 是 `author_insertKey` 的 JSON-RPC 请求，
+//! `params` 为 `[key 类型, secret URI, 公钥]`。插入前会检查 secret URI 和公钥一致，
+//! 并且三个 key 是所选 chain spec 中同一个验证人的 session key。
+
+use codec::{Decode, Encode};
+use node_primitives::AccountId;
+use node_template_runtime::opaque::SessionKeys;
+use sc_cli::{Error, KeystoreParams, Result, SharedParams, SubstrateCli};
+use sc_keystore::LocalKeystore;
+use sc_service::config::{BasePath, KeystoreConfig};
+use serde::Deserialize;
+use sp_core::{
+	bytes::from_hex,
+	crypto::{key_types, KeyTypeId, Ss58Codec},
+	ed25519,
+	hashing::twox_128,
+	sr25519, Pair,
+};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::BuildStorage;
+use std::{fs, path::PathBuf, sync::Arc};
+
+/// `insert-session-keys`
+#[derive(Debug, clap::Parser)]
+pub struct InsertSessionKeysCmd {
+	/// 保存 `author_insertKey` 请求的目录
+	#[clap(long, parse(from_os_str), default_value = "session-keys")]
+	dir: PathBuf,
+
+	/// 验证人的序号，读取 `babe{N}`、`gran{N}` 和 `imon{N}`
+	#[clap(long)]
+	index: u32,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+/// `author_insertKey` 请求
+#[derive(Debug, Deserialize)]
+struct InsertKeyRequest {
+	params: (String, String, String),
+}
+
+/// 从请求文件中读出的 key
+struct SessionKey {
+	key_type: KeyTypeId,
+	suri: String,
+	public: Vec<u8>,
+}
+
+impl InsertSessionKeysCmd {
+	/// 执行子命令
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> Result<()> {
+		let babe = self.read_key::<sr25519::Pair>("babe", key_types::BABE)?;
+		let grandpa = self.read_key::<ed25519::Pair>("gran", key_types::GRANDPA)?;
+		let im_online = self.read_key::<sr25519::Pair>("imon", key_types::IM_ONLINE)?;
+
+		let chain_id = self.shared_params.chain_id(self.shared_params.is_dev());
+		let chain_spec = cli.load_spec(&chain_id)?;
+		let validator = genesis_validator(&*chain_spec, &babe, &grandpa, &im_online)?;
+
+		let base_path = self
+			.shared_params
+			.base_path()
+			.unwrap_or_else(|| BasePath::from_project("", "", &C::executable_name()));
+		let config_dir = base_path.config_dir(chain_spec.id());
+		let keystore: SyncCryptoStorePtr =
+			match self.keystore_params.keystore_config(&config_dir)? {
+				(_, KeystoreConfig::Path { path, password }) =>
+					Arc::new(LocalKeystore::open(path, password)?),
+				_ => unreachable!("keystore_config always returns path and password; qed"),
+			};
+
+		for key in [&babe, &grandpa, &im_online] {
+			SyncCryptoStore::insert_unknown(&*keystore, key.key_type, &key.suri, &key.public)
+				.map_err(|_| Error::KeyStoreOperation)?;
+		}
+		println!(
+			"Inserted the session keys of validator {} of {}",
+			validator.to_ss58check(),
+			chain_spec.name()
+		);
+		Ok(())
+	}
+
+	/// 读取 `{prefix}{index}`，检查 key 类型，并且 secret URI 按 `P` 推导出的公钥和文件中的相同
+	fn read_key<P: Pair>(&self, prefix: &str, key_type: KeyTypeId) -> Result<SessionKey> {
+		let path = self.dir.join(format!("{}{}", prefix, self.index));
+		let request: InsertKeyRequest = serde_json::from_str(&fs::read_to_string(&path)?)
+			.map_err(|e| format!("Invalid key file {}: {}", path.display(), e))?;
+		let (file_key_type, suri, public) = request.params;
+
+		if file_key_type.as_bytes() != key_type.0.as_slice() {
+			return Err(format!(
+				"{}: expected key type {}, found {}",
+				path.display(),
+				String::from_utf8_lossy(&key_type.0),
+				file_key_type
+			)
+			.into())
+		}
+		let pair = P::from_string(&suri, None)
+			.map_err(|e| format!("{}: invalid secret URI: {:?}", path.display(), e))?;
+		let derived = pair.public().as_ref().to_vec();
+		let public = from_hex(&public)
+			.map_err(|e| format!("{}: invalid public key: {}", path.display(), e))?;
+		if derived != public {
+			return Err(
+				format!("{}: public key doesn't match the secret URI", path.display()).into()
+			)
+		}
+
+		Ok(SessionKey { key_type, suri, public })
+	}
+}
+
+/// 在 chain spec 的创世状态中查找 session key 和这三个 key 相同的验证人
+fn genesis_validator(
+	chain_spec: &dyn sc_service::ChainSpec,
+	babe: &SessionKey,
+	grandpa: &SessionKey,
+	im_online: &SessionKey,
+) -> Result<AccountId> {
+	let storage = chain_spec.as_storage_builder().build_storage()?;
+	let key = [twox_128(b"Session"), twox_128(b"QueuedKeys")].concat();
+	let queued = storage.top.get(&key).ok_or("No session keys in the chain spec")?;
+	let validators = Vec::<(AccountId, SessionKeys)>::decode(&mut &queued[..])
+		.map_err(|e| format!("Invalid session keys in the chain spec: {}", e))?;
+
+	let (validator, keys) = validators
+		.into_iter()
+		.find(|(_, keys)| keys.babe.encode() == babe.public)
+		.ok_or_else(|| format!("The BABE key is not an authority of {}", chain_spec.name()))?;
+	if keys.grandpa.encode() != grandpa.public {
+		return Err(format!(
+			"The GRANDPA key is not the one of validator {}",
+			validator.to_ss58check()
+		)
+		.into())
+	}
+	if keys.im_online.encode() != im_online.public {
+		return Err(format!(
+			"The im-online key is not the one of validator {}",
+			validator.to_ss58check()
+		)
+		.into())
+	}
+	Ok(validator)
+}
@@ -8,6 +8,9 @@ mod cli;
 mod command;
 mod rpc;
 mod command_helper;
+mod generate_spec;
+mod insert_session_keys;
+mod staging_spec;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
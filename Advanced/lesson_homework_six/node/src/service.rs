@@ -11,6 +11,8 @@ use crate::rpc::{FullDeps, BabeDeps, GrandpaDeps, create_full};
 use sp_runtime::traits::Block as BlockT;
 use sc_network::NetworkService;
 use sc_consensus_babe::SlotProportion;
+use sp_core::crypto::Ss58Codec;
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -42,8 +44,43 @@ type FullGrandpaBlockImport =
 	sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
-	new_full_base(config, |_, _| ()).map(|NewFullBase { task_manager, .. }| task_manager)
+pub fn new_full(
+	config: Configuration,
+	kitty_worker_suri: Option<String>,
+) -> Result<TaskManager, ServiceError> {
+	new_full_base(config, kitty_worker_suri, |_, _| ())
+		.map(|NewFullBase { task_manager, .. }| task_manager)
+}
+
+/// 插入 kitties 链下工作机的 "kty!" key。
+/// 来源依次是 `--kitty-worker-suri`（或 `--kitty-worker-key-file`）和 `--dev`、`--alice` 等开发账户；
+/// 都没有时使用 keystore 里已有的 key，比如通过 `author_insertKey` 插入的
+fn provision_kitty_worker_key(
+	config: &Configuration,
+	keystore: SyncCryptoStorePtr,
+	suri: Option<String>,
+) -> Result<(), ServiceError> {
+	if !config.offchain_worker.enabled {
+		return Ok(())
+	}
+
+	let key_type = node_template_runtime::pallet_kitties::KEY_TYPE;
+	if let Some(suri) = suri.or_else(|| config.dev_key_seed.clone()) {
+		let public = SyncCryptoStore::sr25519_generate_new(&*keystore, key_type, Some(&suri))
+			.map_err(|e| ServiceError::Other(format!("Invalid kitties offchain worker key: {}", e)))?;
+		log::info!("🐱 Kitties offchain worker key: {}", public.to_ss58check());
+	} else if SyncCryptoStore::sr25519_public_keys(&*keystore, key_type).is_empty() {
+		log::warn!("⚠️  The offchain worker is enabled but there is no kitties \"kty!\" key.");
+		log::warn!(
+			"⚠️  Every `update_kitty` submission will fail with \"No local accounts available\"."
+		);
+		log::warn!(
+			"⚠️  Start the node with `--kitty-worker-suri`, `--kitty-worker-key-file` or `--alice`, \
+			 or insert a key with the `author_insertKey` RPC."
+		);
+	}
+
+	Ok(())
 }
 
 /// Result of [`new_full_base`].
@@ -63,6 +100,7 @@ pub struct NewFullBase {
 /// Creates a full service from the configuration.
 pub fn new_full_base(
 	mut config: Configuration,
+	kitty_worker_suri: Option<String>,
 	with_startup_data: impl FnOnce(
 		&sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
 		&sc_consensus_babe::BabeLink<Block>,
@@ -78,6 +116,7 @@ pub fn new_full_base(
 		transaction_pool,
 		other: (rpc_builder, import_setup, rpc_setup, mut telemetry),
 	} = new_partial(&config)?;
+	provision_kitty_worker_key(&config, keystore_container.sync_keystore(), kitty_worker_suri)?;
 
 	let shared_voter_state = rpc_setup;
 	let grandpa_protocol_name = sc_finality_grandpa::protocol_standard_name(
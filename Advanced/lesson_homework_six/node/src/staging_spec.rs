@@ -0,0 +1,247 @@
+//! `--chain staging` 的参数：验证人、提名人、质押金额和启动节点不再写在代码里。
+//!
+//! 默认使用编译进节点的 `staging-keys.toml`。更换验证人时可以用 `--staging-keys` 指定另一个
+//! key 清单（TOML 或 JSON，格式和 `staging-keys.toml` 相同），也可以用 `--staging-secret`
+//! 按 `subkey inspect "$SECRET//$i//$j"` 的方式推导 key：
+//!
+//! ```bash
+//! substrate-stencil build-spec --chain staging --raw \
+//!     --staging-secret "$SECRET" --staging-validators 4 --staging-nominators 2 \
+//!     --staging-bootnode /ip4/10.0.0.1/tcp/30333/p2p/12D3KooW... > stencil-staging-raw.json
+//! ```
+//!
+//! 第 i 个验证人的 stash、controller、BABE 和 im-online key 是 sr25519 的 `$SECRET//$i//stash`、
+//! `$SECRET//$i//controller`、`$SECRET//$i//babe`、`$SECRET//$i//im_online`，GRANDPA key 是
+//! ed25519 的 `$SECRET//$i//grandpa`；sudo 账户是 `$SECRET/fir`，第 k 个提名人是
+//! `$SECRET//nominator//$k`，提名前 `MaxNominations` 个验证人。
+
+use crate::{
+	chain_spec::{staging_network_config, ChainSpec, StagingConfig, ENDOWMENT, STASH},
+	generate_spec::{account, AuthorityEntry},
+};
+use node_primitives::{AccountId, Balance};
+use node_template_runtime::MaxNominations;
+use sc_service::config::MultiaddrWithPeerId;
+use serde::Deserialize;
+use sp_core::{ed25519, sr25519, Get, Pair};
+use std::{fs, path::PathBuf};
+
+/// 编译进节点的默认 key 清单
+const DEFAULT_KEYS: &str = include_str!("../../staging-keys.toml");
+
+/// 没有 `--staging-validators` 时推导的验证人个数
+const DEFAULT_VALIDATORS: u32 = 4;
+
+/// staging chain spec 的参数，可以写在子命令之前或之后
+#[derive(Debug, clap::Args)]
+pub struct StagingParams {
+	/// staging 网络的 key 清单，`.toml` 或 `.json`，默认使用编译进节点的 `staging-keys.toml`
+	#[clap(long, global = true, value_name = "PATH", parse(from_os_str))]
+	staging_keys: Option<PathBuf>,
+
+	/// 按 `$SECRET//$i//$j` 推导 staging 网络的 key，不能和 `--staging-keys` 同时使用
+	#[clap(long, global = true, value_name = "SECRET")]
+	staging_secret: Option<String>,
+
+	/// 从 `--staging-secret` 推导的验证人个数，默认 4 个
+	#[clap(long, global = true, value_name = "COUNT")]
+	staging_validators: Option<u32>,
+
+	/// 从 `--staging-secret` 推导的提名人个数，默认没有提名人
+	#[clap(long, global = true, value_name = "COUNT")]
+	staging_nominators: Option<u32>,
+
+	/// 每个验证人 stash 的质押金额，覆盖 key 清单中的 `validator_bond`
+	#[clap(long, global = true, value_name = "BALANCE")]
+	staging_bond: Option<Balance>,
+
+	/// 每个提名人的质押金额，覆盖 key 清单中的 `nominator_bond`
+	#[clap(long, global = true, value_name = "BALANCE")]
+	staging_nominator_bond: Option<Balance>,
+
+	/// staging 网络的启动节点，可以指定多次，覆盖 key 清单中的 `boot_nodes`
+	#[clap(long, global = true, value_name = "ADDR")]
+	staging_bootnode: Vec<MultiaddrWithPeerId>,
+}
+
+/// key 清单
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyManifest {
+	/// sudo 账户
+	sudo: String,
+	/// 验证人，stash 会被分配初始余额并质押
+	authorities: Vec<AuthorityEntry>,
+	#[serde(default)]
+	nominators: Vec<NominatorEntry>,
+	/// 除 sudo、验证人和提名人以外的初始余额账户
+	#[serde(default)]
+	endowed_accounts: Vec<String>,
+	/// 默认和开发链相同
+	validator_bond: Option<Balance>,
+	/// 默认和开发链相同
+	nominator_bond: Option<Balance>,
+	#[serde(default)]
+	boot_nodes: Vec<MultiaddrWithPeerId>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NominatorEntry {
+	account: String,
+	/// 提名的验证人 stash
+	targets: Vec<String>,
+}
+
+impl StagingParams {
+	/// 按参数生成 staging chain spec
+	pub fn chain_spec(&self) -> Result<ChainSpec, String> {
+		let mut config = match (&self.staging_secret, &self.staging_keys) {
+			(Some(_), Some(_)) =>
+				return Err("--staging-secret and --staging-keys can't be used together".into()),
+			(Some(secret), None) => self.derive_config(secret)?,
+			(None, keys) => {
+				if self.staging_validators.is_some() || self.staging_nominators.is_some() {
+					return Err(
+						"--staging-validators and --staging-nominators require --staging-secret"
+							.into(),
+					)
+				}
+				match keys {
+					Some(path) => {
+						let text = fs::read_to_string(path)
+							.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+						let manifest = match path.extension().and_then(|ext| ext.to_str()) {
+							Some("toml") => toml::from_str::<KeyManifest>(&text)
+								.map_err(|e| format!("Invalid key manifest: {}", e))?,
+							_ => serde_json::from_str::<KeyManifest>(&text)
+								.map_err(|e| format!("Invalid key manifest: {}", e))?,
+						};
+						manifest.into_config()?
+					},
+					None => toml::from_str::<KeyManifest>(DEFAULT_KEYS)
+						.map_err(|e| format!("Invalid built-in key manifest: {}", e))?
+						.into_config()?,
+				}
+			},
+		};
+
+		if let Some(bond) = self.staging_bond {
+			config.validator_bond = bond;
+		}
+		if let Some(bond) = self.staging_nominator_bond {
+			config.nominator_bond = bond;
+		}
+		if !self.staging_bootnode.is_empty() {
+			config.boot_nodes = self.staging_bootnode.clone();
+		}
+
+		check_config(&config)?;
+		Ok(staging_network_config(config))
+	}
+
+	/// 从 secret 推导验证人、提名人和 sudo 账户
+	fn derive_config(&self, secret: &str) -> Result<StagingConfig, String> {
+		let authorities = (1..=self.staging_validators.unwrap_or(DEFAULT_VALIDATORS))
+			.map(|i| {
+				let path = format!("{}//{}", secret, i);
+				Ok((
+					derive::<sr25519::Pair>(&format!("{}//stash", path))?.into(),
+					derive::<sr25519::Pair>(&format!("{}//controller", path))?.into(),
+					derive::<sr25519::Pair>(&format!("{}//babe", path))?.into(),
+					derive::<ed25519::Pair>(&format!("{}//grandpa", path))?.into(),
+					derive::<sr25519::Pair>(&format!("{}//im_online", path))?.into(),
+				))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+
+		let targets = authorities
+			.iter()
+			.take(MaxNominations::get() as usize)
+			.map(|a| a.0.clone())
+			.collect::<Vec<AccountId>>();
+		let nominators = (1..=self.staging_nominators.unwrap_or(0))
+			.map(|k| {
+				let nominator = derive::<sr25519::Pair>(&format!("{}//nominator//{}", secret, k))?;
+				Ok((nominator.into(), targets.clone()))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+
+		Ok(StagingConfig {
+			authorities,
+			nominators,
+			root_key: derive::<sr25519::Pair>(&format!("{}/fir", secret))?.into(),
+			endowed_accounts: vec![],
+			validator_bond: STASH,
+			nominator_bond: STASH,
+			boot_nodes: vec![],
+		})
+	}
+}
+
+impl KeyManifest {
+	fn into_config(self) -> Result<StagingConfig, String> {
+		Ok(StagingConfig {
+			authorities: self
+				.authorities
+				.iter()
+				.map(AuthorityEntry::keys)
+				.collect::<Result<Vec<_>, String>>()?,
+			nominators: self
+				.nominators
+				.iter()
+				.map(|n| {
+					let targets =
+						n.targets.iter().map(|t| account(t)).collect::<Result<Vec<_>, String>>()?;
+					Ok((account(&n.account)?, targets))
+				})
+				.collect::<Result<Vec<_>, String>>()?,
+			root_key: account(&self.sudo)?,
+			endowed_accounts: self
+				.endowed_accounts
+				.iter()
+				.map(|a| account(a))
+				.collect::<Result<Vec<_>, String>>()?,
+			validator_bond: self.validator_bond.unwrap_or(STASH),
+			nominator_bond: self.nominator_bond.unwrap_or(STASH),
+			boot_nodes: self.boot_nodes,
+		})
+	}
+}
+
+/// 在 chain spec 里检查，避免构建创世状态时 staking 模块 panic
+fn check_config(config: &StagingConfig) -> Result<(), String> {
+	if config.authorities.is_empty() {
+		return Err("At least one staging authority is required".into())
+	}
+	for bond in [config.validator_bond, config.nominator_bond] {
+		if bond == 0 || bond > ENDOWMENT {
+			return Err(format!(
+				"Staking bonds must be between 1 and the endowment ({})",
+				ENDOWMENT
+			))
+		}
+	}
+	for (nominator, targets) in &config.nominators {
+		if targets.is_empty() || targets.len() > MaxNominations::get() as usize {
+			return Err(format!(
+				"Nominator {} must nominate 1 to {} validators",
+				nominator,
+				MaxNominations::get()
+			))
+		}
+		if let Some(target) =
+			targets.iter().find(|t| !config.authorities.iter().any(|a| &a.0 == *t))
+		{
+			return Err(format!("Nomination target {} is not a staging validator stash", target))
+		}
+	}
+	Ok(())
+}
+
+/// secret URI 对应的公钥，出错时不打印 secret
+fn derive<P: Pair>(suri: &str) -> Result<P::Public, String> {
+	P::from_string(suri, None)
+		.map(|pair| pair.public())
+		.map_err(|e| format!("Invalid staging secret: {:?}", e))
+}
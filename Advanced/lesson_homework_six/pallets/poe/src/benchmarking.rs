@@ -0,0 +1,108 @@
+//! Benchmarking setup for pallet-poe
+
+use super::*;
+
+#[allow(unused)]
+use crate::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller, account};
+use frame_support::traits::{Currency, EnsureOrigin, ReservableCurrency, Time};
+use frame_system::RawOrigin;
+
+fn assert_success_event<T: Config>(generic_event: <T as Config>::Event) {
+	frame_system::Pallet::<T>::assert_last_event(generic_event.into());
+}
+
+fn insert_claim<T: Config>(claim: &Vec<u8>, sender: &T::AccountId) {
+	let bounded_claim =
+		BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).expect("claim too long");
+	Proofs::<T>::insert(
+		&bounded_claim,
+		(sender.clone(), frame_system::Pallet::<T>::block_number(), T::Time::now()),
+	);
+}
+
+fn insert_dispute<T: Config>(claim: &Vec<u8>, challenger: &T::AccountId) {
+	let bounded_claim =
+		BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).expect("claim too long");
+	let bond = T::DisputeBond::get();
+	T::Currency::make_free_balance_be(challenger, T::Currency::minimum_balance() + bond + bond);
+	T::Currency::reserve(challenger, bond).expect("challenger is funded");
+	Disputes::<T>::insert(&bounded_claim, (challenger.clone(), bond, BoundedVec::default()));
+}
+
+benchmarks! {
+	create_claim {
+		let d in 0..T::MaxClaimLength::get();
+		let claim = vec![0; d as usize];
+		let caller: T::AccountId = whitelisted_caller();
+	} : _(RawOrigin::Signed(caller.clone()), claim.clone())
+	verify {
+		assert_success_event::<T>(
+			Event::ClaimCreated(caller, claim, T::Time::now()).into()
+		);
+	}
+
+	transfer_claim {
+		let d in 0..T::MaxClaimLength::get();
+		let claim = vec![0; d as usize];
+		let caller: T::AccountId = whitelisted_caller();
+		let dest: T::AccountId = account("dest", 0, 0);
+
+		insert_claim::<T>(&claim, &caller);
+	} : _(RawOrigin::Signed(caller.clone()), claim.clone(), dest.clone())
+	verify {
+		assert_success_event::<T>(
+			Event::ClaimTransferred(caller.clone(), dest.clone(), claim.clone(), T::Time::now()).into()
+		);
+	}
+
+	revoke_claim {
+		let d in 0..T::MaxClaimLength::get();
+		let claim = vec![0; d as usize];
+		let caller: T::AccountId = whitelisted_caller();
+
+		insert_claim::<T>(&claim, &caller);
+	} : _(RawOrigin::Signed(caller.clone()), claim.clone())
+	verify {
+		assert_success_event::<T>(
+			Event::ClaimRevoked(caller, claim).into()
+		);
+	}
+
+	dispute_claim {
+		let d in 0..T::MaxClaimLength::get();
+		let e in 0..T::MaxEvidenceLength::get();
+		let claim = vec![0; d as usize];
+		let evidence = vec![0; e as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		let bond = T::DisputeBond::get();
+		T::Currency::make_free_balance_be(&caller, T::Currency::minimum_balance() + bond + bond);
+
+		insert_claim::<T>(&claim, &owner);
+	} : _(RawOrigin::Signed(caller.clone()), claim.clone(), evidence)
+	verify {
+		assert_success_event::<T>(
+			Event::ClaimDisputed(caller, claim, bond).into()
+		);
+	}
+
+	// 转移是最重的裁决结果
+	resolve_dispute {
+		let d in 0..T::MaxClaimLength::get();
+		let claim = vec![0; d as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let challenger: T::AccountId = account("challenger", 0, 0);
+		let origin = T::DisputeOrigin::successful_origin();
+
+		insert_claim::<T>(&claim, &owner);
+		insert_dispute::<T>(&claim, &challenger);
+	} : _<T::Origin>(origin, claim.clone(), Resolution::Transfer)
+	verify {
+		assert_success_event::<T>(
+			Event::DisputeResolved(challenger, claim, Resolution::Transfer).into()
+		);
+	}
+
+	impl_benchmark_test_suite!(PoeModule, crate::mock::new_test_ext(), crate::mock::Test);
+}
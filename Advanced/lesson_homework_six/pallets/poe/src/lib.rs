@@ -0,0 +1,366 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_system::weights;
+/// a module for proof of existence
+pub use pallet::*;
+pub use weight::WeightInfo;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weight;
+
+/// 存证时间戳的类型，即 `pallet_timestamp` 的 `Moment`
+pub type MomentOf<T> = <<T as Config>::Time as frame_support::traits::Time>::Moment;
+
+/// 争议保证金的类型
+pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+	<T as frame_system::Config>::AccountId,
+>>::Balance;
+
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// 争议的裁决结果
+#[derive(
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	frame_support::RuntimeDebug,
+	scale_info::TypeInfo,
+)]
+pub enum Resolution {
+	/// 争议不成立，罚没发起人的保证金，存证不变
+	Reject,
+	/// 争议成立，存证转移给发起人，退还保证金
+	Transfer,
+	/// 争议成立，吊销存证，退还保证金
+	Revoke,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	pub use frame_support::pallet_prelude::*;
+	pub use frame_system::pallet_prelude::*;
+	pub use sp_std::prelude::*;
+	use frame_support::traits::{OnUnbalanced, ReservableCurrency, Time};
+	use pallet_support::events::IndexedEvents;
+	use super::{BalanceOf, MomentOf, NegativeImbalanceOf, Resolution, WeightInfo};
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	/// 模块配置接口
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+
+		/// The maximum length of chain that can be added
+		#[pallet::constant]
+		/// 定义存证的最大长度，超过了会导致链上到状态爆炸
+		type MaxClaimLength: Get<u32>;
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		// 把runtime定义的系统的类型设置在当前模块，满足的条件，可以从当前模块转移过去，同时是系统模块的Event类型
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		///设置权重值
+		type WeightInfo: WeightInfo;
+
+		/// 提供存证时间的时钟，一般是 pallet_timestamp
+		type Time: Time;
+
+		/// 用于锁定争议保证金的货币
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// 发起争议需要锁定的保证金
+		#[pallet::constant]
+		type DisputeBond: Get<BalanceOf<Self>>;
+
+		/// 争议证据的最大长度
+		#[pallet::constant]
+		type MaxEvidenceLength: Get<u32>;
+
+		/// 裁决争议的来源，比如议会 3/5
+		type DisputeOrigin: EnsureOrigin<Self::Origin>;
+
+		/// 被罚没的保证金的去处，比如国库
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+	}
+
+	#[pallet::pallet]
+	//定义自己所需的存储项所需的宏
+	#[pallet::generate_store(pub(super) trait Store)]
+	//定义模块所需的结构体
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	//#[pallet::getter(fn proofs)]
+	//存储项
+	pub type Proofs<T: Config> = StorageMap<
+		_,
+		// hash算法，用来将存储项存储到底层数据库的时候，对其位置进行计算（密码安全）
+		Blake2_128Concat,
+		// 新版本使用BoundedVec而不是Vec，BoundedVec长度受限的集合类型
+		BoundedVec<u8, T::MaxClaimLength>,
+		// 所有者，存证（或转移）时的区块数和时间戳
+		(T::AccountId, T::BlockNumber, MomentOf<T>)
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn claim_length_limit)]
+	//新存证的最大长度，创世时配置，不能超过 MaxClaimLength，默认等于 MaxClaimLength
+	pub type ClaimLengthLimit<T: Config> = StorageValue<_, u32, ValueQuery, T::MaxClaimLength>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// 新存证的最大长度
+		pub max_claim_length: u32,
+		pub _phantom: PhantomData<T>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { max_claim_length: T::MaxClaimLength::get(), _phantom: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(
+				self.max_claim_length <= T::MaxClaimLength::get(),
+				"max_claim_length can't exceed MaxClaimLength"
+			);
+			ClaimLengthLimit::<T>::put(self.max_claim_length);
+		}
+	}
+
+	#[pallet::storage]
+	//争议中的存证：发起人，锁定的保证金和证据
+	pub type Disputes<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		BoundedVec<u8, T::MaxClaimLength>,
+		(T::AccountId, BalanceOf<T>, BoundedVec<u8, T::MaxEvidenceLength>)
+	>;
+
+	#[pallet::event]
+	//事件通过 deposit_indexed_event 发出，存证和账户会作为 topic 写入 EventTopics
+	pub enum Event<T: Config> {
+		//创建时触发
+		ClaimCreated(T::AccountId, Vec<u8>, MomentOf<T>),
+		//吊销时触发
+		ClaimRevoked(T::AccountId, Vec<u8>),
+		//转移时触发
+		ClaimTransferred(T::AccountId, T::AccountId, Vec<u8>, MomentOf<T>),
+		//发起争议时触发：发起人，存证，锁定的保证金
+		ClaimDisputed(T::AccountId, Vec<u8>, BalanceOf<T>),
+		//裁决争议时触发：发起人，存证，裁决结果
+		DisputeResolved(T::AccountId, Vec<u8>, Resolution),
+	}
+
+	// Errors inform users that something went wrong.
+	#[pallet::error]
+	pub enum Error<T> {
+		ProofAlreadyExist,
+		ClaimTooLong,
+		ClaimNotExist,
+		NotClaimOwner,
+		EvidenceTooLong,
+		DisputeAlreadyExist,
+		DisputeNotExist,
+		CannotDisputeOwnClaim,
+		ClaimUnderDispute,
+		InsufficientBond,
+	}
+
+	#[pallet::hooks]
+	//定义保留函数
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(T::WeightInfo::create_claim(claim.len() as u32))]
+		/// 创建存证可调用函数  origin表示发送方 claim存证的hash值
+		pub fn create_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>
+		) -> DispatchResultWithPostInfo {
+			// 校验是否是一个签名的交易并获取sender
+			let sender = ensure_signed(origin)?;
+			// 校验claim长度
+			ensure!(claim.len() as u32 <= Self::claim_length_limit(), Error::<T>::ClaimTooLong);
+			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+			// 确保不存在
+			ensure!(!Proofs::<T>::contains_key(&bounded_claim), Error::<T>::ProofAlreadyExist);
+			// 存储
+			let now = T::Time::now();
+			Proofs::<T>::insert(
+				&bounded_claim,
+				(sender.clone(), frame_system::Pallet::<T>::block_number(), now)
+			);
+			// 发送一个成功的事件
+			let topics = [T::topic(&claim), T::topic(&sender)];
+			let event = Event::ClaimCreated(sender, claim, now);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
+
+			Ok(().into())
+		}
+
+		#[pallet::weight(T::WeightInfo::revoke_claim(claim.len() as u32))]
+		pub fn revoke_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			// 校验claim长度
+			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+			let (owner, _, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
+			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			// 争议期间所有者不能吊销，避免逃避裁决
+			ensure!(!Disputes::<T>::contains_key(&bounded_claim), Error::<T>::ClaimUnderDispute);
+			Proofs::<T>::remove(&bounded_claim);
+			let topics = [T::topic(&claim), T::topic(&sender)];
+			let event = Event::ClaimRevoked(sender, claim);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
+
+			Ok(().into())
+		}
+
+		#[pallet::weight(T::WeightInfo::transfer_claim(claim.len() as u32))]
+		pub fn transfer_claim(origin: OriginFor<T>, claim: Vec<u8>, dest: T::AccountId) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			// 校验claim长度
+			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+
+			let (owner, _, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
+			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(!Disputes::<T>::contains_key(&bounded_claim), Error::<T>::ClaimUnderDispute);
+
+			let now = T::Time::now();
+			Proofs::<T>::insert(&bounded_claim, (dest.clone(), frame_system::Pallet::<T>::block_number(), now));
+			// 发送事件，声明权证转移
+			let topics = [T::topic(&claim), T::topic(&sender), T::topic(&dest)];
+			let event = Event::ClaimTransferred(sender, dest, claim, now);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
+
+			Ok(().into())
+		}
+
+		#[pallet::weight(T::WeightInfo::dispute_claim(claim.len() as u32, evidence.len() as u32))]
+		/// 对存证发起争议，锁定保证金等待裁决
+		pub fn dispute_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			evidence: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+			let bounded_evidence = BoundedVec::<u8, T::MaxEvidenceLength>::try_from(evidence).map_err(|_| Error::<T>::EvidenceTooLong)?;
+
+			let (owner, _, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExist)?;
+			ensure!(owner != sender, Error::<T>::CannotDisputeOwnClaim);
+			ensure!(!Disputes::<T>::contains_key(&bounded_claim), Error::<T>::DisputeAlreadyExist);
+
+			// 锁定保证金
+			let bond = T::DisputeBond::get();
+			T::Currency::reserve(&sender, bond).map_err(|_| Error::<T>::InsufficientBond)?;
+
+			Disputes::<T>::insert(&bounded_claim, (sender.clone(), bond, bounded_evidence));
+			let topics = [T::topic(&claim), T::topic(&sender)];
+			let event = Event::ClaimDisputed(sender, claim, bond);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
+
+			Ok(().into())
+		}
+
+		#[pallet::weight(T::WeightInfo::resolve_dispute(claim.len() as u32))]
+		/// 裁决争议，只能由 `DisputeOrigin` 调用
+		pub fn resolve_dispute(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			resolution: Resolution,
+		) -> DispatchResultWithPostInfo {
+			T::DisputeOrigin::ensure_origin(origin)?;
+			let bounded_claim = BoundedVec::<u8, T::MaxClaimLength>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+			let (challenger, bond, _) = Disputes::<T>::take(&bounded_claim).ok_or(Error::<T>::DisputeNotExist)?;
+
+			match resolution {
+				Resolution::Reject => {
+					// 罚没保证金
+					let (imbalance, _) = T::Currency::slash_reserved(&challenger, bond);
+					T::OnSlash::on_unbalanced(imbalance);
+				},
+				Resolution::Transfer => {
+					T::Currency::unreserve(&challenger, bond);
+					Proofs::<T>::insert(
+						&bounded_claim,
+						(challenger.clone(), frame_system::Pallet::<T>::block_number(), T::Time::now())
+					);
+				},
+				Resolution::Revoke => {
+					T::Currency::unreserve(&challenger, bond);
+					Proofs::<T>::remove(&bounded_claim);
+				},
+			}
+			let topics = [T::topic(&claim), T::topic(&challenger)];
+			let event = Event::DisputeResolved(challenger, claim, resolution);
+			T::deposit_indexed_event(&topics, <T as Config>::Event::from(event));
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 存储的不变量：`ClaimLengthLimit` 不超过 `MaxClaimLength`，存证的块不晚于当前块，
+		/// 争议中的存证存在且不是发起人的，发起人锁定的余额不少于保证金
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			use frame_support::sp_runtime::traits::Saturating;
+			use sp_std::collections::btree_map::BTreeMap;
+
+			ensure!(
+				Self::claim_length_limit() <= T::MaxClaimLength::get(),
+				"ClaimLengthLimit exceeds MaxClaimLength"
+			);
+			let now = frame_system::Pallet::<T>::block_number();
+			for (_, (_, block_number, _)) in Proofs::<T>::iter() {
+				ensure!(block_number <= now, "Proof recorded in a future block");
+			}
+
+			let mut bonds = BTreeMap::<T::AccountId, BalanceOf<T>>::new();
+			for (claim, (challenger, bond, _)) in Disputes::<T>::iter() {
+				let (owner, _, _) = Proofs::<T>::get(&claim).ok_or("Disputed claim not exist")?;
+				ensure!(owner != challenger, "Claim disputed by its owner");
+				let total = bonds.entry(challenger).or_default();
+				*total = total.saturating_add(bond);
+			}
+			for (challenger, bond) in bonds {
+				ensure!(
+					T::Currency::reserved_balance(&challenger) >= bond,
+					"Dispute bond not reserved"
+				);
+			}
+			Ok(())
+		}
+	}
+}
\ No newline at end of file
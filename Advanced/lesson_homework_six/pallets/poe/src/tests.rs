@@ -0,0 +1,354 @@
+use super::*;
+use crate::{mock::{self, *}, Error};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GenesisBuild, ReservableCurrency},
+	BoundedVec,
+};
+use pallet_support::events::IndexedEvents;
+
+#[test]
+fn create_claim_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, frame_system::Pallet::<Test>::block_number(), 0)));
+	})
+}
+
+#[test]
+fn create_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(1_656_000_000_000);
+		let claim = vec![0, 1];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, 1, 1_656_000_000_000)));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimCreated(1, claim, 1_656_000_000_000)));
+	})
+}
+
+#[test]
+fn create_claim_failed_when_claim_already_exist() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 1];
+		let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::ProofAlreadyExist
+		);
+
+	})
+}
+
+#[test]
+fn create_claim_failed_when_claim_too_long() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0;513];
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::ClaimTooLong
+		);
+
+	})
+}
+
+#[test]
+fn create_claim_respects_genesis_claim_length_limit() {
+	new_test_ext().execute_with(|| {
+		// 没有配置时等于 MaxClaimLength
+		assert_eq!(PoeModule::claim_length_limit(), 512);
+	});
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { max_claim_length: 4, _phantom: Default::default() }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_noop!(
+			PoeModule::create_claim(Origin::signed(1), vec![0; 5]),
+			Error::<Test>::ClaimTooLong
+		);
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0; 4]));
+	});
+}
+
+#[test]
+fn revoke_claim_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 2];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+	})
+}
+
+#[test]
+fn revoke_claim_when_not_claim_owner() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 2];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(2), claim.clone()),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+#[test]
+fn revoke_claim_when_claim_not_exist() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 2];
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::ClaimNotExist
+		);
+	})
+}
+
+#[test]
+fn transfer_claim_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 3];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+	})
+}
+
+#[test]
+fn transfer_claim_records_timestamp() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(1_000);
+		let claim = vec![0, 3];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+		System::set_block_number(2);
+		Timestamp::set_timestamp(7_000);
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((2, 2, 7_000)));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimTransferred(1, 2, claim, 7_000)));
+	})
+}
+
+#[test]
+fn transfer_claim_when_claim_not_exist() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 3];
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2),
+			Error::<Test>::ClaimNotExist
+		);
+	})
+}
+
+#[test]
+fn transfer_claim_when_not_claim_owner() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 3];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(2), claim.clone(), 1),
+			Error::<Test>::NotClaimOwner
+		);
+	})
+}
+
+#[test]
+fn dispute_claim_works() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![7; 8]));
+
+		assert_eq!(Balances::reserved_balance(2), 100);
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		let (challenger, bond, evidence) = Disputes::<Test>::get(&bounded_claim).unwrap();
+		assert_eq!((challenger, bond, evidence.into_inner()), (2, 100, vec![7; 8]));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimDisputed(2, claim, 100)));
+	})
+}
+
+#[test]
+fn dispute_claim_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]),
+			Error::<Test>::ClaimNotExist
+		);
+
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(1), claim.clone(), vec![]),
+			Error::<Test>::CannotDisputeOwnClaim
+		);
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![0; 1025]),
+			Error::<Test>::EvidenceTooLong
+		);
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(3), claim.clone(), vec![]),
+			Error::<Test>::InsufficientBond
+		);
+
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_noop!(
+			PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]),
+			Error::<Test>::DisputeAlreadyExist
+		);
+	})
+}
+
+#[test]
+fn disputed_claim_can_not_be_revoked_or_transferred() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+
+		assert_noop!(
+			PoeModule::revoke_claim(Origin::signed(1), claim.clone()),
+			Error::<Test>::ClaimUnderDispute
+		);
+		assert_noop!(
+			PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 3),
+			Error::<Test>::ClaimUnderDispute
+		);
+	})
+}
+
+#[test]
+fn resolve_dispute_requires_dispute_origin() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+
+		assert_noop!(
+			PoeModule::resolve_dispute(Origin::signed(1), claim.clone(), Resolution::Reject),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			PoeModule::resolve_dispute(Origin::root(), vec![0, 6], Resolution::Reject),
+			Error::<Test>::DisputeNotExist
+		);
+	})
+}
+
+#[test]
+fn resolve_dispute_reject_slashes_bond() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_ok!(PoeModule::resolve_dispute(Origin::root(), claim.clone(), Resolution::Reject));
+
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 900);
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim).map(|(owner, _, _)| owner), Some(1));
+		assert!(!Disputes::<Test>::contains_key(&bounded_claim));
+	})
+}
+
+#[test]
+fn resolve_dispute_transfer_refunds_bond() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+
+		System::set_block_number(2);
+		Timestamp::set_timestamp(6_000);
+		assert_ok!(PoeModule::resolve_dispute(Origin::root(), claim.clone(), Resolution::Transfer));
+
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 1_000);
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((2, 2, 6_000)));
+		System::assert_last_event(mock::Event::PoeModule(crate::Event::DisputeResolved(2, claim, Resolution::Transfer)));
+	})
+}
+
+#[test]
+fn resolve_dispute_revoke_refunds_bond() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_ok!(PoeModule::resolve_dispute(Origin::root(), claim.clone(), Resolution::Revoke));
+
+		assert_eq!(Balances::free_balance(2), 1_000);
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim.clone()).unwrap();
+		assert!(!Proofs::<Test>::contains_key(&bounded_claim));
+	})
+}
+
+#[test]
+fn claim_events_are_indexed_by_claim_and_accounts() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let alice: u64 = 1;
+		let bob: u64 = 2;
+		let claim: Vec<u8> = vec![0, 6];
+		assert_ok!(PoeModule::create_claim(Origin::signed(alice), claim.clone()));
+		assert_ok!(PoeModule::transfer_claim(Origin::signed(alice), claim.clone(), bob));
+		assert_ok!(PoeModule::revoke_claim(Origin::signed(bob), claim.clone()));
+
+		assert_eq!(System::event_topics(&Test::topic(&claim)), vec![(1, 0), (1, 1), (1, 2)]);
+		assert_eq!(System::event_topics(&Test::topic(&alice)), vec![(1, 0), (1, 1)]);
+		assert_eq!(System::event_topics(&Test::topic(&bob)), vec![(1, 1), (1, 2)]);
+		let other_claim: Vec<u8> = vec![0, 7];
+		assert!(System::event_topics(&Test::topic(&other_claim)).is_empty());
+	})
+}
+
+#[test]
+fn dispute_events_are_indexed_by_claim_and_challenger() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		let challenger: u64 = 2;
+		let claim: Vec<u8> = vec![0, 6];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		System::reset_events();
+
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(challenger), claim.clone(), vec![]));
+		assert_ok!(PoeModule::resolve_dispute(Origin::root(), claim.clone(), Resolution::Revoke));
+
+		// 锁定和退还保证金也会发出 Balances 事件
+		let indexes: Vec<u32> = System::events()
+			.iter()
+			.enumerate()
+			.filter(|(_, record)| matches!(record.event, mock::Event::PoeModule(_)))
+			.map(|(index, _)| index as u32)
+			.collect();
+		let expected: Vec<(u64, u32)> = indexes.into_iter().map(|index| (1, index)).collect();
+		assert_eq!(System::event_topics(&Test::topic(&claim)), expected);
+		assert_eq!(System::event_topics(&Test::topic(&challenger)), expected);
+	})
+}
+
+#[test]
+fn try_state_checks_disputes() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![0, 5];
+		assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(PoeModule::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_ok!(PoeModule::do_try_state());
+
+		// 保证金没有锁定
+		Balances::unreserve(&2, 100);
+		assert_eq!(PoeModule::do_try_state(), Err("Dispute bond not reserved"));
+
+		// 争议中的存证被删除
+		let bounded_claim = BoundedVec::<u8, <Test as Config>::MaxClaimLength>::try_from(claim).unwrap();
+		Proofs::<Test>::remove(&bounded_claim);
+		assert_eq!(PoeModule::do_try_state(), Err("Disputed claim not exist"));
+	})
+}
@@ -0,0 +1,156 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weights for pallet_poe.
+//!
+//! NOT generated by the benchmark CLI for this runtime. `create_claim` is the lesson_homework_one
+//! result (2022-10-08, `dev` chain, 20 steps, 10 repeats), `transfer_claim` and `revoke_claim` are
+//! those results with the `Disputes` read added by hand, and `dispute_claim` and `resolve_dispute`
+//! are estimates. Replace this file with the output of:
+//!
+//! ```text
+//! ./target/release/substrate-stencil benchmark pallet --chain dev --execution=wasm \
+//!     --wasm-execution=compiled --pallet pallet_poe --extrinsic '*' --steps 20 --repeat 10 \
+//!     --output ./pallets/poe/src/weight.rs
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_poe.
+pub trait WeightInfo {
+	fn create_claim(d: u32, ) -> Weight;
+	fn transfer_claim(d: u32, ) -> Weight;
+	fn revoke_claim(d: u32, ) -> Weight;
+	fn dispute_claim(d: u32, e: u32, ) -> Weight;
+	fn resolve_dispute(d: u32, ) -> Weight;
+}
+
+/// Weights for pallet_poe using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: PoeModule Proofs (r:1 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	fn create_claim(d: u32, ) -> Weight {
+		(12_690_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((5_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 512]`.
+	fn transfer_claim(d: u32, ) -> Weight {
+		(13_058_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((9_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 512]`.
+	fn revoke_claim(d: u32, ) -> Weight {
+		(12_426_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((8_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	/// The range of component `e` is `[0, 1024]`.
+	fn dispute_claim(d: u32, e: u32, ) -> Weight {
+		(32_000_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((2_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PoeModule Proofs (r:0 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	fn resolve_dispute(d: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: PoeModule Proofs (r:1 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	fn create_claim(d: u32, ) -> Weight {
+		(12_690_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((5_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 512]`.
+	fn transfer_claim(d: u32, ) -> Weight {
+		(13_058_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((9_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 512]`.
+	fn revoke_claim(d: u32, ) -> Weight {
+		(12_426_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((8_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	/// The range of component `e` is `[0, 1024]`.
+	fn dispute_claim(d: u32, e: u32, ) -> Weight {
+		(32_000_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((2_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PoeModule Proofs (r:0 w:1)
+	/// The range of component `d` is `[0, 512]`.
+	fn resolve_dispute(d: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((9_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+}
\ No newline at end of file
@@ -18,3 +18,16 @@ fn correct_error_for_none_value() {
 		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
 	});
 }
+
+#[test]
+fn try_state_checks_something() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::do_try_state());
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
+		assert_ok!(TemplateModule::do_try_state());
+
+		// 写入无法解码的值
+		frame_support::storage::unhashed::put_raw(&crate::Something::<Test>::hashed_key(), &[1]);
+		assert_eq!(TemplateModule::do_try_state(), Err("Something can't be decoded"));
+	});
+}
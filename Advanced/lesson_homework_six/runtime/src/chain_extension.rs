@@ -0,0 +1,83 @@
+//! 让 ink! 合约读取和转移 kitty 的 chain extension。
+//!
+//! 合约端的接口是 `lesson_homework_five/kitties_extension`，两边的函数 id 和状态码必须一致：
+//!
+//! | id | 函数        | 输入                      | 输出                  |
+//! |----|-------------|---------------------------|-----------------------|
+//! | 1  | `owner_of`  | kitty id `u32`            | `Option<AccountId>`   |
+//! | 2  | `kitty_dna` | kitty id `u32`            | `Option<[u8; 16]>`    |
+//! | 3  | `transfer`  | `(u32, AccountId)`        | 状态码                |
+//!
+//! `transfer` 以合约账户作为 kitty 的所有者，合约只能转移自己拥有的 kitty。
+//! 状态码 0 表示成功，失败时是 [`TRANSFER_ERRORS`] 中的位置加 1，其他错误会让合约执行失败。
+
+use crate::{KittiesModule, Runtime};
+use codec::Encode;
+use frame_support::traits::Get;
+use node_primitives::AccountId;
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_kitties::Error;
+use sp_runtime::DispatchError;
+
+/// 查询 kitty 的所有者
+pub const OWNER_OF: u32 = 1;
+/// 读取 kitty 的 dna
+pub const KITTY_DNA: u32 = 2;
+/// 转移合约拥有的 kitty
+pub const TRANSFER: u32 = 3;
+
+/// `transfer` 返回给合约的错误，状态码是位置加 1
+pub const TRANSFER_ERRORS: [Error<Runtime>; 4] =
+	[Error::NotExistKittyId, Error::NotOwner, Error::NotEnoughBalance, Error::OwnTooManyKitties];
+
+/// `pallet_contracts::Config::ChainExtension` 使用的 kitties 接口
+pub struct KittiesExtension;
+
+impl ChainExtension<Runtime> for KittiesExtension {
+	fn call<E>(func_id: u32, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+		let mut env = env.buf_in_buf_out();
+		match func_id {
+			OWNER_OF => {
+				env.charge_weight(db_weight.reads(1))?;
+				let kitty_id: u32 = env.read_as()?;
+				env.write(&KittiesModule::kitty_owner(kitty_id).encode(), false, None)?;
+			},
+			KITTY_DNA => {
+				env.charge_weight(db_weight.reads(1))?;
+				let kitty_id: u32 = env.read_as()?;
+				let dna = KittiesModule::kitties(kitty_id).map(|kitty| kitty.dna);
+				env.write(&dna.encode(), false, None)?;
+			},
+			TRANSFER => {
+				// 读：kitty、所有者、两个账户、两个 kitty 列表和事件计数；
+				// 写：所有者、两个账户、两个 kitty 列表，事件、事件计数和事件的三个 topic
+				env.charge_weight(db_weight.reads_writes(7, 10))?;
+				let (kitty_id, new_owner): (u32, AccountId) = env.read_as()?;
+				let contract = env.ext().address().clone();
+				let status = match KittiesModule::do_transfer(contract, kitty_id, new_owner) {
+					Ok(()) => 0,
+					Err(e) => status_code(e)?,
+				};
+				return Ok(RetVal::Converging(status))
+			},
+			_ => return Err(DispatchError::Other("Unknown kitties chain extension function")),
+		}
+		Ok(RetVal::Converging(0))
+	}
+}
+
+/// 把 kitties 模块的错误转换为状态码，其他错误原样返回
+fn status_code(error: DispatchError) -> Result<u32, DispatchError> {
+	TRANSFER_ERRORS
+		.into_iter()
+		.position(|e| DispatchError::from(e) == error)
+		.map(|i| i as u32 + 1)
+		.ok_or(error)
+}
@@ -0,0 +1,135 @@
+//! 在 runtime 上部署 `lesson_homework_five` 的 erc20 合约并调用 `transfer`。
+//!
+//! 合约用 `cargo contract build --release` 编译，需要安装 cargo-contract 1.x；
+//! 也可以用 `ERC20_WASM` 指定已经编译好的 Wasm 文件。
+
+use codec::{Decode, Encode};
+use node_primitives::{AccountId, Balance};
+use node_template_runtime::{constants::currency::DOLLARS, Contracts, Event, Runtime, System};
+use pallet_contracts_primitives::Code;
+use sp_core::hashing::blake2_256;
+use std::{env, fs, path::Path, process::Command};
+
+const ALICE: [u8; 32] = [1; 32];
+const BOB: [u8; 32] = [2; 32];
+
+const GAS_LIMIT: u64 = 100_000_000_000;
+
+/// 足够支付上传代码和合约存储的押金
+const BALANCE: Balance = 1_000_000 * DOLLARS;
+
+/// 部署时给 Alice 的代币数量
+const TOTAL_SUPPLY: Balance = 1_000_000;
+
+/// erc20 的 `Transfer` 事件，是合约的第一个事件
+#[derive(Debug, PartialEq, Decode)]
+struct Transfer {
+	from: Option<AccountId>,
+	to: Option<AccountId>,
+	value: Balance,
+}
+
+fn account(id: [u8; 32]) -> AccountId {
+	id.into()
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(account(ALICE), BALANCE), (account(BOB), BALANCE)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// erc20 合约的 Wasm，没有设置 `ERC20_WASM` 时先编译合约
+fn erc20_wasm() -> Vec<u8> {
+	if let Some(path) = env::var_os("ERC20_WASM") {
+		return fs::read(path).expect("ERC20_WASM is readable")
+	}
+
+	let manifest =
+		Path::new(env!("CARGO_MANIFEST_DIR")).join("../../lesson_homework_five/erc20/Cargo.toml");
+	let status = Command::new("cargo")
+		.args(["contract", "build", "--release", "--manifest-path"])
+		.arg(&manifest)
+		// 合约输出到自己的 target 目录，不用测试的
+		.env_remove("CARGO_TARGET_DIR")
+		.status()
+		.expect("cargo-contract 1.x is installed");
+	assert!(status.success(), "Failed to build the erc20 contract: {}", status);
+	fs::read(manifest.with_file_name("target/ink/erc20.wasm")).unwrap()
+}
+
+/// ink! 的 selector 是名字的 blake2-256 哈希的前四个字节
+fn selector(name: &str) -> Vec<u8> {
+	blake2_256(name.as_bytes())[..4].to_vec()
+}
+
+/// 当前块中合约发出的 `Transfer` 事件
+fn emitted_transfers() -> Vec<Transfer> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			Event::Contracts(pallet_contracts::Event::ContractEmitted { data, .. })
+				if data.first() == Some(&0) =>
+				Transfer::decode(&mut &data[1..]).ok(),
+			_ => None,
+		})
+		.collect()
+}
+
+/// 以 `caller` 的身份调用合约的 `message`，返回 SCALE 编码的返回值
+fn call(caller: [u8; 32], contract: &AccountId, message: &str, args: impl Encode) -> Vec<u8> {
+	let data = [selector(message), args.encode()].concat();
+	let result =
+		Contracts::bare_call(account(caller), contract.clone(), 0, GAS_LIMIT, None, data, false)
+			.result
+			.unwrap();
+	assert!(!result.did_revert(), "{} reverted", message);
+	result.data.to_vec()
+}
+
+#[test]
+fn erc20_is_deployed_and_transfers() {
+	let code = erc20_wasm();
+	new_test_ext().execute_with(|| {
+		let code_hash = Contracts::bare_upload_code(account(ALICE), code, None).unwrap().code_hash;
+
+		let contract = Contracts::bare_instantiate(
+			account(ALICE),
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Existing(code_hash),
+			[selector("new"), TOTAL_SUPPLY.encode()].concat(),
+			vec![],
+			false,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		assert_eq!(
+			emitted_transfers(),
+			vec![Transfer { from: None, to: Some(account(ALICE)), value: TOTAL_SUPPLY }]
+		);
+
+		System::reset_events();
+		// `transfer` 返回 `Result<(), Error>`，`Ok(())` 编码为 0
+		let result = call(ALICE, &contract, "transfer", (account(BOB), 100 as Balance));
+		assert_eq!(result, vec![0]);
+		assert_eq!(
+			emitted_transfers(),
+			vec![Transfer { from: Some(account(ALICE)), to: Some(account(BOB)), value: 100 }]
+		);
+
+		let balance = call(BOB, &contract, "balance_of", account(BOB));
+		assert_eq!(Balance::decode(&mut &balance[..]).unwrap(), 100);
+		let balance = call(BOB, &contract, "balance_of", account(ALICE));
+		assert_eq!(Balance::decode(&mut &balance[..]).unwrap(), TOTAL_SUPPLY - 100);
+	});
+}
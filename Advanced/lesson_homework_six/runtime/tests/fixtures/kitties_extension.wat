@@ -0,0 +1,37 @@
+;; 把输入转发给 chain extension 的合约：输入是 u32 的函数 id 和函数的输入，
+;; 返回 u32 的状态码和函数的输出
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) 输入的长度，初始为缓冲区大小 256
+	;; [4, 8) 输出的长度，初始为缓冲区大小 256
+	;; [16, 272) 输入：函数 id 和函数的输入
+	;; [272, 276) 状态码
+	;; [276, 532) 函数的输出
+	(data (i32.const 0) "\00\01\00\00\00\01\00\00")
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 16) (i32.const 0))
+		(i32.store
+			(i32.const 272)
+			(call $seal_call_chain_extension
+				(i32.load (i32.const 16))
+				(i32.const 20)
+				(i32.sub (i32.load (i32.const 0)) (i32.const 4))
+				(i32.const 276)
+				(i32.const 4)
+			)
+		)
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 272)
+			(i32.add (i32.load (i32.const 4)) (i32.const 4))
+		)
+	)
+)
@@ -0,0 +1,110 @@
+//! 合约通过 chain extension 查询和转移 kitty。
+//!
+//! `fixtures/kitties_extension.wat` 把调用的输入原样转发给 chain extension，
+//! 返回状态码和 chain extension 的输出。
+
+use codec::{Decode, Encode};
+use frame_support::{assert_ok, traits::GenesisBuild};
+use node_primitives::{AccountId, Balance};
+use node_template_runtime::{
+	chain_extension::{KITTY_DNA, OWNER_OF, TRANSFER},
+	constants::currency::DOLLARS,
+	Balances, Contracts, KittiesModule, KittyPrice, Origin, Runtime, System,
+};
+
+const ALICE: [u8; 32] = [1; 32];
+const BOB: [u8; 32] = [2; 32];
+
+const BALANCE: Balance = 1_000 * DOLLARS;
+const DNA: [u8; 16] = [7; 16];
+const GAS_LIMIT: u64 = 100_000_000_000;
+
+fn account(id: [u8; 32]) -> AccountId {
+	id.into()
+}
+
+/// Alice 在创世时拥有 kitty 0
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(account(ALICE), BALANCE), (account(BOB), BALANCE)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_kitties::GenesisConfig::<Runtime> { kitties: vec![(account(ALICE), DNA)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Alice 部署合约，并转给合约足够质押一个 kitty 的余额
+fn instantiate() -> AccountId {
+	let wasm = wat::parse_str(include_str!("fixtures/kitties_extension.wat")).unwrap();
+	Contracts::bare_instantiate(
+		account(ALICE),
+		10 * DOLLARS,
+		GAS_LIMIT,
+		None,
+		pallet_contracts_primitives::Code::Upload(wasm.into()),
+		vec![],
+		vec![],
+		false,
+	)
+	.result
+	.unwrap()
+	.account_id
+}
+
+/// 以 Alice 的身份调用合约，返回 chain extension 的状态码和输出
+fn call_extension(contract: &AccountId, func_id: u32, input: impl Encode) -> (u32, Vec<u8>) {
+	let data = (func_id, input).encode();
+	let result =
+		Contracts::bare_call(account(ALICE), contract.clone(), 0, GAS_LIMIT, None, data, false)
+			.result
+			.unwrap();
+	let status = u32::decode(&mut &result.data[..4]).unwrap();
+	(status, result.data[4..].to_vec())
+}
+
+#[test]
+fn contract_reads_kitties() {
+	new_test_ext().execute_with(|| {
+		let contract = instantiate();
+
+		let (status, owner) = call_extension(&contract, OWNER_OF, 0u32);
+		assert_eq!(status, 0);
+		assert_eq!(Option::<AccountId>::decode(&mut &owner[..]).unwrap(), Some(account(ALICE)));
+
+		let (status, dna) = call_extension(&contract, KITTY_DNA, 0u32);
+		assert_eq!(status, 0);
+		assert_eq!(Option::<[u8; 16]>::decode(&mut &dna[..]).unwrap(), Some(DNA));
+
+		let (_, owner) = call_extension(&contract, OWNER_OF, 1u32);
+		assert_eq!(Option::<AccountId>::decode(&mut &owner[..]).unwrap(), None);
+	});
+}
+
+#[test]
+fn contract_transfers_own_kitty() {
+	new_test_ext().execute_with(|| {
+		let contract = instantiate();
+
+		// kitty 0 属于 Alice，合约不能转移，状态码 2 是 NotOwner
+		let (status, _) = call_extension(&contract, TRANSFER, (0u32, account(BOB)));
+		assert_eq!(status, 2);
+		assert_eq!(KittiesModule::kitty_owner(0), Some(account(ALICE)));
+
+		assert_ok!(KittiesModule::transfer(Origin::signed(account(ALICE)), 0, contract.clone()));
+		let (status, _) = call_extension(&contract, TRANSFER, (0u32, account(BOB)));
+		assert_eq!(status, 0);
+		assert_eq!(KittiesModule::kitty_owner(0), Some(account(BOB)));
+		assert_eq!(Balances::reserved_balance(&account(BOB)), KittyPrice::get());
+
+		// 不存在的 kitty，状态码 1 是 NotExistKittyId
+		let (status, _) = call_extension(&contract, TRANSFER, (1u32, account(BOB)));
+		assert_eq!(status, 1);
+	});
+}
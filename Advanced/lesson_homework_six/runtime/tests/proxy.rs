@@ -0,0 +1,100 @@
+//! 代理类型允许代理的调用。
+
+use frame_support::traits::InstanceFilter;
+use node_primitives::AccountId;
+use node_template_runtime::{
+	constants::currency::DOLLARS, BalancesCall, Call, ProxyType, SystemCall,
+};
+
+const BOB: [u8; 32] = [2; 32];
+
+fn bob() -> AccountId {
+	BOB.into()
+}
+
+fn transfer() -> Call {
+	BalancesCall::transfer { dest: bob().into(), value: DOLLARS }.into()
+}
+
+fn contract_call() -> Call {
+	pallet_contracts::Call::call {
+		dest: bob().into(),
+		value: 0,
+		gas_limit: 1_000_000,
+		storage_deposit_limit: None,
+		data: vec![],
+	}
+	.into()
+}
+
+fn kitty_transfer() -> Call {
+	pallet_kitties::Call::transfer { kitty_id: 0, new_owner: bob() }.into()
+}
+
+fn kitty_create() -> Call {
+	pallet_kitties::Call::create {}.into()
+}
+
+fn remark() -> Call {
+	SystemCall::remark { remark: vec![1] }.into()
+}
+
+fn propose() -> Call {
+	pallet_democracy::Call::propose { proposal_hash: [1; 32].into(), value: DOLLARS }.into()
+}
+
+fn chill() -> Call {
+	pallet_staking::Call::chill {}.into()
+}
+
+#[test]
+fn any_allows_all_calls() {
+	for call in [
+		transfer(),
+		contract_call(),
+		kitty_transfer(),
+		kitty_create(),
+		remark(),
+		propose(),
+		chill(),
+	] {
+		assert!(ProxyType::Any.filter(&call), "{:?}", call);
+	}
+}
+
+#[test]
+fn non_transfer_rejects_transfers_and_contract_calls() {
+	for call in [transfer(), contract_call(), kitty_transfer()] {
+		assert!(!ProxyType::NonTransfer.filter(&call), "{:?}", call);
+	}
+	for call in [kitty_create(), remark(), propose(), chill()] {
+		assert!(ProxyType::NonTransfer.filter(&call), "{:?}", call);
+	}
+}
+
+#[test]
+fn governance_and_staking_allow_only_their_calls() {
+	assert!(ProxyType::Governance.filter(&propose()));
+	assert!(ProxyType::Staking.filter(&chill()));
+	for call in [transfer(), contract_call(), kitty_transfer(), kitty_create(), remark()] {
+		assert!(!ProxyType::Governance.filter(&call), "{:?}", call);
+		assert!(!ProxyType::Staking.filter(&call), "{:?}", call);
+	}
+	assert!(!ProxyType::Governance.filter(&chill()));
+	assert!(!ProxyType::Staking.filter(&propose()));
+}
+
+#[test]
+fn is_superset_follows_the_filters() {
+	use ProxyType::*;
+
+	for proxy in [Any, NonTransfer, Governance, Staking] {
+		assert!(Any.is_superset(&proxy));
+		assert!(proxy.is_superset(&proxy));
+	}
+	assert!(NonTransfer.is_superset(&Governance));
+	assert!(NonTransfer.is_superset(&Staking));
+	assert!(!NonTransfer.is_superset(&Any));
+	assert!(!Governance.is_superset(&Staking));
+	assert!(!Staking.is_superset(&NonTransfer));
+}
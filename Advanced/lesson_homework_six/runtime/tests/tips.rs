@@ -0,0 +1,113 @@
+//! 打赏模块在 runtime 中的完整流程：举报、议会成员打赏、倒计时结束后从国库付款。
+
+use frame_support::{assert_noop, assert_ok, traits::GenesisBuild};
+use node_primitives::{AccountId, Balance, Hash};
+use node_template_runtime::{
+	constants::currency::DOLLARS, Balances, DataDepositPerByte, Event, Origin, Runtime, System,
+	TipCountdown, TipReportDepositBase, Tips, Treasury,
+};
+
+const ALICE: [u8; 32] = [1; 32];
+const BOB: [u8; 32] = [2; 32];
+const CHARLIE: [u8; 32] = [3; 32];
+const DAVE: [u8; 32] = [4; 32];
+const FERDIE: [u8; 32] = [5; 32];
+
+const BALANCE: Balance = 1_000 * DOLLARS;
+
+fn account(id: [u8; 32]) -> AccountId {
+	id.into()
+}
+
+/// Bob、Charlie 和 Dave 是议会成员，国库有足够的余额
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![
+			(account(ALICE), BALANCE),
+			(account(BOB), BALANCE),
+			(account(CHARLIE), BALANCE),
+			(account(DAVE), BALANCE),
+			(account(FERDIE), BALANCE),
+			(Treasury::account_id(), BALANCE),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_elections_phragmen::GenesisConfig::<Runtime> {
+		members: vec![
+			(account(BOB), 10 * DOLLARS),
+			(account(CHARLIE), 10 * DOLLARS),
+			(account(DAVE), 10 * DOLLARS),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn new_tip_hash() -> Hash {
+	System::events()
+		.into_iter()
+		.find_map(|record| match record.event {
+			Event::Tips(pallet_tips::Event::NewTip { tip_hash }) => Some(tip_hash),
+			_ => None,
+		})
+		.expect("NewTip event")
+}
+
+#[test]
+fn report_tip_and_close() {
+	new_test_ext().execute_with(|| {
+		let reason = b"fixed the staging spec".to_vec();
+		let deposit =
+			TipReportDepositBase::get() + DataDepositPerByte::get() * reason.len() as u128;
+
+		assert_ok!(Tips::report_awesome(Origin::signed(account(ALICE)), reason, account(FERDIE)));
+		assert_eq!(Balances::reserved_balance(&account(ALICE)), deposit);
+		let hash = new_tip_hash();
+
+		// 三个成员中有两个打赏后开始倒计时
+		assert_ok!(Tips::tip(Origin::signed(account(BOB)), hash, 10 * DOLLARS));
+		assert_ok!(Tips::tip(Origin::signed(account(CHARLIE)), hash, 20 * DOLLARS));
+		System::assert_has_event(Event::Tips(pallet_tips::Event::TipClosing { tip_hash: hash }));
+
+		assert_noop!(
+			Tips::close_tip(Origin::signed(account(DAVE)), hash),
+			pallet_tips::Error::<Runtime>::Premature
+		);
+		System::set_block_number(1 + TipCountdown::get());
+		assert_ok!(Tips::close_tip(Origin::signed(account(DAVE)), hash));
+
+		// 支付打赏的中位数，其中 20% 作为举报人的奖励
+		System::assert_has_event(Event::Tips(pallet_tips::Event::TipClosed {
+			tip_hash: hash,
+			who: account(FERDIE),
+			payout: 16 * DOLLARS,
+		}));
+		assert_eq!(Balances::free_balance(&account(FERDIE)), BALANCE + 16 * DOLLARS);
+		assert_eq!(Balances::free_balance(&account(ALICE)), BALANCE + 4 * DOLLARS);
+		assert_eq!(Balances::reserved_balance(&account(ALICE)), 0);
+		assert_eq!(Balances::free_balance(&Treasury::account_id()), BALANCE - 20 * DOLLARS);
+	});
+}
+
+#[test]
+fn only_council_members_tip() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tips::report_awesome(
+			Origin::signed(account(ALICE)),
+			b"reason".to_vec(),
+			account(FERDIE)
+		));
+		let hash = new_tip_hash();
+
+		assert_noop!(
+			Tips::tip(Origin::signed(account(ALICE)), hash, 10 * DOLLARS),
+			sp_runtime::traits::BadOrigin
+		);
+	});
+}
@@ -1,4 +1,5 @@
 import { ApiPromise,WsProvider,Keyring } from "@polkadot/api";
+import { blake2AsHex } from "@polkadot/util-crypto";
 
 /// Don't use localhost
 /// Don't use wss it meas ssl for websocket!
@@ -60,6 +61,34 @@ const subscribeEvents = async (api:ApiPromise) => {
     });
   });
 }
+/// PoE 和 kitties 的事件 topic 是 blake2_256(SCALE 编码的值)
+/// 支持 claim:0x..., account:5..., kitty:3
+const topicOf = (api:ApiPromise, filter: string) => {
+    const [kind, value] = filter.split(":");
+    switch (kind) {
+        case "claim": return blake2AsHex(api.createType("Bytes", value).toU8a());
+        case "account": return blake2AsHex(api.createType("AccountId", value).toU8a());
+        case "kitty": return blake2AsHex(api.createType("u32", value).toU8a());
+        default: throw new Error(`unknown topic filter: ${filter}`);
+    }
+}
+
+const subscribeEventsByTopic = async (api:ApiPromise, filter: string) => {
+    const topic = topicOf(api, filter);
+    console.log(`Subscribe to events with topic ${filter} (${topic})`);
+
+    // EventTopics 只记录当前块的事件序号，和 Events 一起查询保证是同一个块
+    api.queryMulti([
+        api.query.system.events,
+        [api.query.system.eventTopics, topic],
+    ], ([events, topics]: any[]) => {
+        topics.forEach(([blockNumber, index]: any[]) => {
+            const { event } = events[index.toNumber()];
+            console.log(`\t#${blockNumber} ${event.section}:${event.method}:: ${event.data.toString()}`);
+        });
+    });
+}
+
 const sleep = async (time:number) => {
     return new Promise(resolve => setTimeout(resolve, time));
 }
@@ -70,7 +99,13 @@ const main = async ()=> {
     const d = await printBalance(api)
     console.log("connection executed~")
     await subscribeAliceAndBob(api)
-    await subscribeEvents(api)
+    // yarn dev claim:0x0102 只订阅该存证相关的事件
+    const filter = process.argv[2]
+    if (filter) {
+        await subscribeEventsByTopic(api, filter)
+    } else {
+        await subscribeEvents(api)
+    }
     await sleep(600000)
 
     console.log("successfuly exit")